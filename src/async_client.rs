@@ -0,0 +1,227 @@
+//! A non-blocking version of [`crate::BggClient`].
+//!
+//! This uses the same parsing and retry logic as the blocking client, with `reqwest` for HTTP and
+//! `tokio` for sleeping between retries.
+use crate::request::{RequestResult, Retry};
+use crate::{
+    CollectionItem, Game, HttpResponse, MAX_IDS, RateLimiter, RetryPolicy, collection,
+    collection_url, error, log_headers, request, thing, thing_url, user_agent,
+};
+use log::debug;
+use reqwest::Client;
+
+/// Async client for the [BGG XML API2](https://boardgamegeek.com/wiki/page/BGG_XML_API2)
+///
+/// Requires the `async` feature. The methods mirror [`crate::BggClient`].
+pub struct AsyncBggClient {
+    client: Client,
+    url: String,
+    api_token: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+}
+
+impl AsyncBggClient {
+    /// Creates an [`AsyncBggClient`] with the specified URL as the base.
+    ///
+    /// See [`crate::BggClient::from_url`].
+    pub fn from_url(url: &str, api_token: &str) -> error::Result<Self> {
+        let client = Client::builder().user_agent(user_agent()).build()?;
+        Ok(Self {
+            client,
+            url: url.to_owned(),
+            api_token: api_token.to_owned(),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+        })
+    }
+
+    /// Creates an [`AsyncBggClient`].
+    pub fn new(api_token: &str) -> error::Result<Self> {
+        Self::from_url("https://boardgamegeek.com", api_token)
+    }
+
+    /// Uses `retry_policy` instead of the default [`RetryPolicy`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Waits for `rate_limiter` before every request. See [`crate::BggClient::with_rate_limiter`].
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Get a user's collection.
+    ///
+    /// See [`crate::BggClient::get_collection`].
+    pub async fn get_collection(
+        &self,
+        user: &str,
+        include_expansions: bool,
+    ) -> error::Result<Vec<CollectionItem>> {
+        self.fetch(
+            &collection_url(&self.url, user, include_expansions),
+            collection::from_xml,
+        )
+        .await
+    }
+
+    /// Get games.
+    ///
+    /// See [`crate::BggClient::get_games`].
+    pub async fn get_games(&self, ids: &[u32]) -> error::Result<Vec<Game>> {
+        let mut games = Vec::new();
+        let total = ids.len();
+        let mut count = 0;
+
+        for chunk in ids.chunks(MAX_IDS as usize) {
+            count += chunk.len();
+            debug!("Getting games ({count} / {total})");
+            games.extend(
+                self.fetch(&thing_url(&self.url, chunk), thing::from_xml)
+                    .await?,
+            );
+        }
+
+        Ok(games)
+    }
+
+    /// Get all games for a user.
+    ///
+    /// See [`crate::BggClient::get_all_games_for_user`].
+    pub async fn get_all_games_for_user(
+        &self,
+        user: &str,
+        include_expansions: bool,
+    ) -> error::Result<Vec<Game>> {
+        let ids = self
+            .get_collection(user, include_expansions)
+            .await?
+            .into_iter()
+            .map(|item| item.id)
+            .collect::<Vec<u32>>();
+
+        self.get_games(&ids).await
+    }
+
+    /// Calls the XML API, with retries, and parses the response with `parse`.
+    async fn fetch<T>(
+        &self,
+        url: &str,
+        parse: impl Fn(&str) -> error::Result<T>,
+    ) -> error::Result<T> {
+        let mut retry = Retry::new(&self.retry_policy);
+
+        loop {
+            let wait = request::rate_limit(self.rate_limiter.as_ref());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+            let delay = match self.get(url).await {
+                Ok(response) => {
+                    log_headers(&response.headers);
+                    match request::handle_response(&response, url, &parse)? {
+                        RequestResult::Done(t) => return Ok(t),
+                        RequestResult::NotDone(not_done) => retry.after_status(&not_done, url)?,
+                    }
+                }
+                Err(error) => retry.after_error(error)?,
+            };
+            tokio::time::sleep(delay).await
+        }
+    }
+
+    async fn get(&self, url: &str) -> error::Result<HttpResponse> {
+        let response = self
+            .client
+            .get(url)
+            .bearer_auth(&self.api_token)
+            .send()
+            .await?;
+
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            headers: response
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_string(),
+                        value.to_str().unwrap_or("not a string").to_owned(),
+                    )
+                })
+                .collect(),
+            body: response.text().await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{MockResponse, MockServer};
+    use crate::{AsyncBggClient, RetryPolicy};
+    use std::time::Duration;
+
+    fn assert_send<T: Send>(_: T) {}
+
+    // Web frameworks such as axum need the futures to be Send.
+    #[test]
+    fn test_futures_are_send() {
+        let client = AsyncBggClient::new("API_TOKEN").expect("Creating client");
+
+        assert_send(client.get_collection("unixgeek", false));
+        assert_send(client.get_games(&[246900]));
+        assert_send(client.get_all_games_for_user("unixgeek", false));
+    }
+
+    #[test]
+    fn test_get_all_games_for_user() {
+        let server = MockServer::start().expect("Starting server");
+        server.on(
+            "/xmlapi2/collection",
+            [
+                MockResponse::status(202),
+                MockResponse::fixture("test/unixgeek.xml").expect("Reading fixture"),
+            ],
+        );
+        server.on(
+            "/xmlapi2/thing",
+            [MockResponse::fixture("test/enormity.xml").expect("Reading fixture")],
+        );
+        let client = AsyncBggClient::from_url(&server.url(), "API_TOKEN")
+            .expect("Creating client")
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::ZERO,
+                ..RetryPolicy::default()
+            });
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Building runtime");
+        let games = runtime
+            .block_on(client.get_all_games_for_user("unixgeek", false))
+            .expect("Getting games");
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].name, "Enormity");
+        let requests = server.requests();
+        assert_eq!(
+            requests
+                .iter()
+                .map(|request| request.url.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "/xmlapi2/collection?username=unixgeek&own=1&brief=1&subtype=boardgame&excludesubtype=boardgameexpansion",
+                "/xmlapi2/collection?username=unixgeek&own=1&brief=1&subtype=boardgame&excludesubtype=boardgameexpansion",
+                "/xmlapi2/thing?id=421,228660,39567&stats=1",
+            ]
+        );
+        assert_eq!(
+            requests[0].header("authorization"),
+            Some("Bearer API_TOKEN")
+        );
+    }
+}
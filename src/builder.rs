@@ -0,0 +1,272 @@
+//! Configuring the HTTP connection of a [`BggClient`].
+use crate::error;
+use crate::error::Error::HttpError;
+use crate::{BggClient, UreqTransport};
+use std::fmt;
+use std::time::Duration;
+use ureq::Agent;
+use ureq::Proxy;
+use ureq::tls::{PemItem, RootCerts, TlsConfig};
+
+/// Builds a [`BggClient`] that sends requests with a configured [`UreqTransport`].
+///
+/// Use [`BggClient::with_transport`] instead to use a different HTTP client.
+///
+/// ```no_run
+/// # use bgg_lib::{error, BggClient};
+/// # use std::time::Duration;
+/// # fn main() -> error::Result<()> {
+/// let client = BggClient::builder()
+///     .with_api_token("API_TOKEN")
+///     .with_user_agent_suffix("my-app/1.0 (me@example.com)")
+///     .with_proxy("http://proxy.example.com:3128")
+///     .with_connect_timeout(Duration::from_secs(5))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct BggClientBuilder {
+    url: String,
+    api_token: Option<String>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    proxy: Option<String>,
+    user_agent_suffix: Option<String>,
+    headers: Vec<(String, String)>,
+    root_certificates: Option<Vec<u8>>,
+}
+
+impl Default for BggClientBuilder {
+    fn default() -> Self {
+        Self {
+            url: "https://boardgamegeek.com".to_owned(),
+            api_token: None,
+            connect_timeout: None,
+            read_timeout: None,
+            proxy: None,
+            user_agent_suffix: None,
+            headers: Vec::new(),
+            root_certificates: None,
+        }
+    }
+}
+
+// Not derived, so the API token isn't logged.
+impl fmt::Debug for BggClientBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BggClientBuilder")
+            .field("url", &self.url)
+            .field("api_token", &self.api_token.as_ref().map(|_| "<redacted>"))
+            .field("connect_timeout", &self.connect_timeout)
+            .field("read_timeout", &self.read_timeout)
+            .field("proxy", &self.proxy)
+            .field("user_agent_suffix", &self.user_agent_suffix)
+            .field("headers", &self.headers)
+            .field("root_certificates", &self.root_certificates.is_some())
+            .finish()
+    }
+}
+
+impl BggClientBuilder {
+    /// Creates a [`BggClientBuilder`] for <https://boardgamegeek.com>, without an API token.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `url` as the base. See [`BggClient::from_url`].
+    pub fn with_base_url(mut self, url: &str) -> Self {
+        self.url = url.to_owned();
+        self
+    }
+
+    /// Sends `api_token` with every request.
+    pub fn with_api_token(mut self, api_token: &str) -> Self {
+        self.api_token = Some(api_token.to_owned());
+        self
+    }
+
+    /// Fails a request if connecting takes longer than `timeout`. There is no timeout by default.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Fails a request if receiving the response, or its body, takes longer than `timeout`. There
+    /// is no timeout by default.
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends requests through the HTTP(S) proxy at `proxy`, e.g. `http://proxy.example.com:3128`.
+    ///
+    /// By default, the proxy is read from the `ALL_PROXY`, `HTTPS_PROXY` and `HTTP_PROXY`
+    /// environment variables.
+    pub fn with_proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_owned());
+        self
+    }
+
+    /// Appends `suffix` to the user agent, e.g. to identify your application as BGG asks.
+    pub fn with_user_agent_suffix(mut self, suffix: &str) -> Self {
+        self.user_agent_suffix = Some(suffix.to_owned());
+        self
+    }
+
+    /// Sends the header `name: value` with every request.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Trusts only the certificates in `pem` instead of Mozilla's root certificates, e.g. for a
+    /// proxy that intercepts TLS.
+    pub fn with_root_certificates(mut self, pem: &[u8]) -> Self {
+        self.root_certificates = Some(pem.to_vec());
+        self
+    }
+
+    /// Builds the [`BggClient`].
+    ///
+    /// Fails with [`error::Error::HttpError`] if the proxy is invalid, or the root certificates
+    /// can't be parsed.
+    pub fn build(self) -> error::Result<BggClient> {
+        let transport = UreqTransport::from_agent(self.agent()?);
+
+        Ok(BggClient {
+            api_token: self.api_token,
+            headers: self.headers,
+            ..BggClient::with_transport(&self.url, "", transport)
+        })
+    }
+
+    fn agent(&self) -> error::Result<Agent> {
+        let mut config = Agent::config_builder()
+            .http_status_as_error(false)
+            .user_agent(self.user_agent())
+            .timeout_connect(self.connect_timeout)
+            .timeout_recv_response(self.read_timeout)
+            .timeout_recv_body(self.read_timeout);
+
+        if let Some(proxy) = &self.proxy {
+            config = config.proxy(Some(Proxy::new(proxy)?));
+        }
+
+        if let Some(pem) = &self.root_certificates {
+            let certificates = ureq::tls::parse_pem(pem)
+                .filter_map(|item| match item {
+                    Ok(PemItem::Certificate(certificate)) => Some(Ok(certificate)),
+                    Ok(_) => None,
+                    Err(error) => Some(Err(error)),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if certificates.is_empty() {
+                return Err(HttpError(
+                    "No certificates in the root certificates PEM".into(),
+                ));
+            }
+
+            config = config.tls_config(
+                TlsConfig::builder()
+                    .root_certs(RootCerts::new_with_certs(&certificates))
+                    .build(),
+            );
+        }
+
+        Ok(config.build().into())
+    }
+
+    fn user_agent(&self) -> String {
+        match &self.user_agent_suffix {
+            Some(suffix) => format!("{} {suffix}", crate::user_agent()),
+            None => crate::user_agent(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::BggClientBuilder;
+    use crate::error::Error::HttpError;
+    use std::fs;
+    use std::time::Duration;
+    use ureq::config::AutoHeaderValue;
+    use ureq::tls::RootCerts;
+
+    #[test]
+    fn test_build() {
+        let client = BggClientBuilder::new()
+            .with_base_url("http://localhost")
+            .with_api_token("API_TOKEN")
+            .with_header("X-Team", "games")
+            .build()
+            .expect("Building client");
+
+        assert_eq!(client.url, "http://localhost");
+        assert_eq!(client.api_token, Some("API_TOKEN".to_owned()));
+        assert_eq!(
+            client.headers,
+            vec![("X-Team".to_owned(), "games".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_debug_redacts_api_token() {
+        let builder = BggClientBuilder::new().with_api_token("API_TOKEN");
+
+        let debug = format!("{builder:?}");
+
+        assert!(!debug.contains("API_TOKEN"));
+        assert!(debug.contains("api_token: Some(\"<redacted>\")"));
+    }
+
+    #[test]
+    fn test_agent() {
+        let pem = fs::read("test/root-ca.pem").expect("Reading file");
+        let agent = BggClientBuilder::new()
+            .with_connect_timeout(Duration::from_secs(5))
+            .with_read_timeout(Duration::from_secs(30))
+            .with_proxy("http://proxy.example.com:3128")
+            .with_user_agent_suffix("my-app/1.0")
+            .with_root_certificates(&pem)
+            .agent()
+            .expect("Building agent");
+        let config = agent.config();
+
+        assert_eq!(config.timeouts().connect, Some(Duration::from_secs(5)));
+        assert_eq!(
+            config.timeouts().recv_response,
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(config.timeouts().recv_body, Some(Duration::from_secs(30)));
+        assert_eq!(
+            config.proxy().map(|proxy| proxy.host()),
+            Some("proxy.example.com")
+        );
+        match config.user_agent() {
+            AutoHeaderValue::Provided(user_agent) => {
+                assert!(user_agent.starts_with("bgg-lib "));
+                assert!(user_agent.ends_with(" my-app/1.0"));
+            }
+            _ => panic!("User agent not provided"),
+        }
+        match config.tls_config().root_certs() {
+            RootCerts::Specific(certificates) => assert_eq!(certificates.len(), 1),
+            _ => panic!("Root certificates not specific"),
+        }
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let result = BggClientBuilder::new()
+            .with_proxy("ftp://proxy.example.com")
+            .build();
+        assert!(matches!(result, Err(HttpError(_))));
+
+        let result = BggClientBuilder::new()
+            .with_root_certificates(b"not a certificate")
+            .build();
+        assert!(matches!(result, Err(HttpError(_))));
+    }
+}
@@ -0,0 +1,344 @@
+//! Caching of XML API responses.
+//!
+//! The raw XML is cached rather than the parsed data, so parsing improvements also apply to cached
+//! responses. Only responses that were parsed successfully are cached.
+use crate::Game;
+use log::debug;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Stores XML API responses, keyed by URL.
+///
+/// Implementations should treat errors as cache misses, since the response can always be
+/// requested again.
+pub trait Cache: Send + Sync {
+    /// Returns the XML stored for `key`, if it was stored less than `max_age` ago.
+    fn get(&self, key: &str, max_age: Duration) -> Option<String>;
+
+    /// Stores `xml` for `key`, replacing anything already stored.
+    fn put(&self, key: &str, xml: &str);
+}
+
+/// Stores games by id, so [`crate::BggClient::get_games`] only requests the games it doesn't
+/// have.
+///
+/// [`Game`] implements `Serialize` and `Deserialize`, so implementations can store games in any
+/// format, e.g. to keep them across runs.
+pub trait GameCache: Send + Sync {
+    /// Returns the game stored for `id`, if it was stored less than `max_age` ago.
+    fn get(&self, id: u32, max_age: Duration) -> Option<Game>;
+
+    /// Stores `game`, replacing any game already stored with the same id.
+    fn put(&self, game: &Game);
+}
+
+/// How long responses are cached, by endpoint.
+#[derive(Clone, Debug)]
+pub struct CacheTtls {
+    /// For `/collection`. The default is 1 hour.
+    pub collection: Duration,
+    /// For `/thing`, and for games in a [`GameCache`]. The default is 1 day.
+    pub thing: Duration,
+}
+
+impl Default for CacheTtls {
+    fn default() -> Self {
+        Self {
+            collection: Duration::from_secs(60 * 60),
+            thing: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// An in-memory [`Cache`] that keeps the most recently used responses.
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<MemoryEntries>,
+}
+
+#[derive(Default)]
+struct MemoryEntries {
+    by_key: HashMap<String, MemoryEntry>,
+    // Incremented on every use, so the entry with the lowest value is the least recently used.
+    clock: u64,
+}
+
+struct MemoryEntry {
+    xml: String,
+    stored: Instant,
+    used: u64,
+}
+
+impl MemoryCache {
+    /// Creates a [`MemoryCache`] that holds up to `capacity` responses.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(MemoryEntries::default()),
+        }
+    }
+}
+
+impl Cache for MemoryCache {
+    fn get(&self, key: &str, max_age: Duration) -> Option<String> {
+        let mut entries = crate::lock(&self.entries);
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        let entry = entries.by_key.get_mut(key)?;
+        if entry.stored.elapsed() >= max_age {
+            return None;
+        }
+        entry.used = clock;
+        Some(entry.xml.clone())
+    }
+
+    fn put(&self, key: &str, xml: &str) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = crate::lock(&self.entries);
+        entries.clock += 1;
+        let clock = entries.clock;
+
+        if !entries.by_key.contains_key(key) && entries.by_key.len() >= self.capacity {
+            let least_recently_used = entries
+                .by_key
+                .iter()
+                .min_by_key(|(_, entry)| entry.used)
+                .map(|(key, _)| key.clone());
+            if let Some(least_recently_used) = least_recently_used {
+                entries.by_key.remove(&least_recently_used);
+            }
+        }
+
+        entries.by_key.insert(
+            key.to_owned(),
+            MemoryEntry {
+                xml: xml.to_owned(),
+                stored: Instant::now(),
+                used: clock,
+            },
+        );
+    }
+}
+
+/// An in-memory [`GameCache`].
+#[derive(Default)]
+pub struct MemoryGameCache {
+    games: Mutex<HashMap<u32, (Game, Instant)>>,
+}
+
+impl MemoryGameCache {
+    /// Creates an empty [`MemoryGameCache`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl GameCache for MemoryGameCache {
+    fn get(&self, id: u32, max_age: Duration) -> Option<Game> {
+        let games = crate::lock(&self.games);
+        let (game, stored) = games.get(&id)?;
+        if stored.elapsed() >= max_age {
+            return None;
+        }
+
+        Some(game.clone())
+    }
+
+    fn put(&self, game: &Game) {
+        crate::lock(&self.games).insert(game.id, (game.clone(), Instant::now()));
+    }
+}
+
+/// Counts the temporary files written by [`FileCache`] in this process.
+static TEMPORARY_FILES: AtomicU64 = AtomicU64::new(0);
+
+/// A [`Cache`] that stores each response in a file in a directory, so it lasts across runs.
+///
+/// The age of a response is the modified time of its file.
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    /// Creates a [`FileCache`] in `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{:016x}.xml", fnv1a(key)))
+    }
+}
+
+impl Cache for FileCache {
+    fn get(&self, key: &str, max_age: Duration) -> Option<String> {
+        let path = self.path(key);
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified());
+        let age = SystemTime::now().duration_since(modified.ok()?).ok()?;
+        if age >= max_age {
+            return None;
+        }
+
+        fs::read_to_string(path).ok()
+    }
+
+    fn put(&self, key: &str, xml: &str) {
+        let path = self.path(key);
+        // Write to a temporary file first, so other processes never read part of a response. The
+        // name is unique, so writers of the same key don't write to the same temporary file.
+        let temporary = path.with_extension(format!(
+            "{}-{}.tmp",
+            std::process::id(),
+            TEMPORARY_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        if let Err(error) = fs::write(&temporary, xml).and_then(|_| fs::rename(&temporary, &path)) {
+            debug!("Error caching {key} in {}: {error}", path.display());
+            let _ = fs::remove_file(&temporary);
+        }
+    }
+}
+
+/// A stable hash for file names. `DefaultHasher` may change between Rust releases.
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Game;
+    use crate::cache::{Cache, FileCache, GameCache, MemoryCache, MemoryGameCache, fnv1a};
+    use std::fs;
+    use std::thread;
+    use std::time::Duration;
+
+    const AN_HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn test_memory_cache() {
+        let cache = MemoryCache::new(2);
+
+        cache.put("a", "<a/>");
+        assert_eq!(cache.get("a", AN_HOUR), Some("<a/>".to_owned()));
+        assert_eq!(cache.get("a", Duration::ZERO), None);
+        assert_eq!(cache.get("b", AN_HOUR), None);
+    }
+
+    #[test]
+    fn test_memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+
+        cache.put("a", "<a/>");
+        cache.put("b", "<b/>");
+        cache.get("a", AN_HOUR);
+        cache.put("c", "<c/>");
+
+        assert_eq!(cache.get("a", AN_HOUR), Some("<a/>".to_owned()));
+        assert_eq!(cache.get("b", AN_HOUR), None);
+        assert_eq!(cache.get("c", AN_HOUR), Some("<c/>".to_owned()));
+    }
+
+    #[test]
+    fn test_memory_cache_poisoned() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", "<a/>");
+
+        thread::scope(|scope| {
+            let poisoner = scope.spawn(|| {
+                let _entries = cache.entries.lock();
+                panic!("Poisoning the cache");
+            });
+            assert!(poisoner.join().is_err());
+        });
+        assert!(cache.entries.is_poisoned());
+
+        assert_eq!(cache.get("a", AN_HOUR), Some("<a/>".to_owned()));
+        cache.put("b", "<b/>");
+        assert_eq!(cache.get("b", AN_HOUR), Some("<b/>".to_owned()));
+    }
+
+    #[test]
+    fn test_file_cache() {
+        let dir = std::env::temp_dir().join(format!("bgg-lib-test-{}", std::process::id()));
+        let cache = FileCache::new(&dir).expect("Creating cache");
+
+        cache.put("a", "<a/>");
+        cache.put("a", "<a>again</a>");
+        assert_eq!(cache.get("a", AN_HOUR), Some("<a>again</a>".to_owned()));
+        assert_eq!(cache.get("a", Duration::ZERO), None);
+        assert_eq!(cache.get("b", AN_HOUR), None);
+        // Only the cached responses are left, not temporary files.
+        assert_eq!(fs::read_dir(&dir).expect("Reading cache").count(), 1);
+
+        fs::remove_dir_all(dir).expect("Removing cache");
+    }
+
+    #[test]
+    fn test_memory_game_cache() {
+        let cache = MemoryGameCache::new();
+
+        cache.put(&Game {
+            id: 421,
+            is_expansion: false,
+            name: "1830".to_owned(),
+            min_player_count: 2,
+            max_player_count: 7,
+            voter_count: 120,
+            best_player_counts: vec![4, 5],
+            rating: 7.9,
+        });
+
+        assert_eq!(
+            cache.get(421, AN_HOUR).map(|game| game.name),
+            Some("1830".to_owned())
+        );
+        assert!(cache.get(421, Duration::ZERO).is_none());
+        assert!(cache.get(39567, AN_HOUR).is_none());
+    }
+
+    #[test]
+    fn test_memory_game_cache_poisoned() {
+        let cache = MemoryGameCache::new();
+
+        thread::scope(|scope| {
+            let poisoner = scope.spawn(|| {
+                let _games = cache.games.lock();
+                panic!("Poisoning the cache");
+            });
+            assert!(poisoner.join().is_err());
+        });
+        assert!(cache.games.is_poisoned());
+
+        cache.put(&Game {
+            id: 421,
+            is_expansion: false,
+            name: "1830".to_owned(),
+            min_player_count: 2,
+            max_player_count: 7,
+            voter_count: 120,
+            best_player_counts: vec![4, 5],
+            rating: 7.9,
+        });
+        assert_eq!(
+            cache.get(421, AN_HOUR).map(|game| game.name),
+            Some("1830".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
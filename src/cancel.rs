@@ -0,0 +1,76 @@
+//! Cancelling calls that are in progress.
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Cancels calls that use it, see [`crate::FetchOptions::with_cancellation`].
+///
+/// Cloning is cheap, and clones cancel the same calls, so keep a clone to cancel from another
+/// thread. Cancelling interrupts waiting between retries, but not a request that has already been
+/// sent.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl CancellationToken {
+    /// Creates a [`CancellationToken`] that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the calls that use this token. They return [`crate::error::Error::Cancelled`].
+    pub fn cancel(&self) {
+        let (cancelled, condvar) = &*self.inner;
+        *crate::lock(cancelled) = true;
+        condvar.notify_all();
+    }
+
+    /// Returns true if [`Self::cancel`] was called.
+    pub fn is_cancelled(&self) -> bool {
+        let (cancelled, _) = &*self.inner;
+        *crate::lock(cancelled)
+    }
+
+    /// Sleeps for `duration`, or until cancelled. Returns true if cancelled.
+    pub(super) fn sleep(&self, duration: Duration) -> bool {
+        let (cancelled, condvar) = &*self.inner;
+        let cancelled = crate::lock(cancelled);
+        let (cancelled, _) = condvar
+            .wait_timeout_while(cancelled, duration, |cancelled| !*cancelled)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        *cancelled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cancel::CancellationToken;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_sleep() {
+        let token = CancellationToken::new();
+
+        assert!(!token.sleep(Duration::from_millis(1)));
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn test_sleep_cancelled() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        let start = Instant::now();
+
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            clone.cancel();
+        });
+
+        assert!(token.sleep(Duration::from_secs(60)));
+        assert!(start.elapsed() < Duration::from_secs(60));
+        assert!(token.is_cancelled());
+        handle.join().unwrap();
+    }
+}
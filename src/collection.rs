@@ -1,33 +1,13 @@
 //! The response from the `/collection` endpoint.
-use crate::error;
-use crate::error::Error::{InvalidUserError, XmlApiError, XmlError};
+use crate::{error, xml};
 use serde::{Deserialize, Serialize};
 
 pub(super) fn from_xml(xml: &str) -> error::Result<Vec<Item>> {
     #[cfg(feature = "moar-debug")]
     log::debug!("Collection XML: {}", xml);
 
-    if xml.contains("<errors>") {
-        let errors = serde_xml::<ErrorResponses>(xml)?.inner;
-
-        if errors.len() == 1 && errors[0].message == "Invalid username specified" {
-            Err(InvalidUserError)
-        } else {
-            let message = errors
-                .into_iter()
-                .map(|error| error.message)
-                .collect::<Vec<_>>()
-                .join(", ");
-            Err(XmlApiError(message))
-        }
-    } else {
-        Ok(serde_xml::<Items>(xml)?.inner)
-    }
-}
-
-fn serde_xml<'a, T: Deserialize<'a>>(xml: &str) -> error::Result<T> {
-    serde_xml_rs::from_str::<T>(xml)
-        .map_err(|error| XmlError(format!("Error deserializing xml: {error}")))
+    xml::check_errors("collection", xml)?;
+    Ok(xml::from_str::<Items>("collection", xml)?.inner)
 }
 
 /// Represents a user's collection.
@@ -44,21 +24,9 @@ struct Items {
     inner: Vec<Item>,
 }
 
-#[derive(Deserialize)]
-#[serde(rename = "error")]
-struct ErrorResponse {
-    message: String,
-}
-
-#[derive(Deserialize)]
-struct ErrorResponses {
-    #[serde(rename = "error")]
-    inner: Vec<ErrorResponse>,
-}
-
 #[cfg(test)]
 mod tests {
-    use crate::collection::{ErrorResponses, Items, from_xml};
+    use crate::collection::{Items, from_xml};
     use crate::error::Error::{InvalidUserError, XmlApiError};
     use std::fs;
 
@@ -92,20 +60,6 @@ mod tests {
         assert_eq!(items.inner.len(), 0);
     }
 
-    #[test]
-    fn test_deserialize_error_response() {
-        let error_responses: ErrorResponses = serde_xml_rs::from_str(
-            &fs::read_to_string("test/invalid-username.xml").expect("Reading file"),
-        )
-        .expect("Parsing XML");
-
-        assert_eq!(error_responses.inner.len(), 1);
-        assert_eq!(
-            error_responses.inner[0].message,
-            "Invalid username specified"
-        );
-    }
-
     #[test]
     fn test_from_xml_invalid_user_error() {
         let result =
@@ -7,14 +7,71 @@ pub enum Error {
     /// The user does not exist in the XML API.
     InvalidUserError,
     #[error("{0}")]
-    /// There was an error processing XML.
-    XmlError(String),
-    #[error("{0}")]
     /// There was an error making the HTTP request.
-    HttpError(#[from] ureq::Error),
+    HttpError(#[from] Box<dyn std::error::Error + Send + Sync>),
     #[error("{0}")]
-    /// The XML API responded with an error.
+    /// The XML API responded with an error message.
     XmlApiError(String),
+    #[error("Unexpected status code {status} for {url}")]
+    /// The XML API responded with a status code that was not retried.
+    UnexpectedStatus {
+        status: u16,
+        url: String,
+        /// The start of the response body, which may explain the status.
+        body_snippet: String,
+    },
+    #[error("Too many retries ({attempts} attempts)")]
+    /// The request was still failing after the maximum number of attempts of the
+    /// [`crate::RetryPolicy`].
+    RetriesExhausted {
+        attempts: u32,
+        /// The status code of the last response, or `None` if there was no response.
+        last_status: Option<u16>,
+        /// The error of the last attempt, if there was no response.
+        #[source]
+        last_error: Option<Box<Error>>,
+    },
+    #[error("Unauthorized, check the API token")]
+    /// The XML API responded with `401 Unauthorized`, e.g. the API token is missing or invalid.
+    Unauthorized,
+    #[error("Forbidden, the API token is not allowed to access this")]
+    /// The XML API responded with `403 Forbidden`, e.g. the API token was revoked.
+    Forbidden,
+    #[error("Not found")]
+    /// The requested thing, guild, person, etc. does not exist.
+    NotFound,
+    #[error(
+        "Error parsing {endpoint}{item}: {source}",
+        item = item_id.map(|id| format!(" item {id}")).unwrap_or_default()
+    )]
+    /// The response could not be parsed.
+    ParseError {
+        /// The endpoint of the response, e.g. `thing`.
+        endpoint: &'static str,
+        /// The id of the item that could not be parsed, if the error is specific to one item.
+        item_id: Option<u32>,
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("Cancelled")]
+    /// The call was cancelled with a [`crate::CancellationToken`].
+    Cancelled,
+    #[error("Deadline exceeded")]
+    /// The call did not finish before the deadline in [`crate::FetchOptions`].
+    DeadlineExceeded,
+}
+
+impl From<ureq::Error> for Error {
+    fn from(error: ureq::Error) -> Self {
+        Error::HttpError(Box::new(error))
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Error::HttpError(Box::new(error))
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
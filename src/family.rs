@@ -0,0 +1,133 @@
+//! The response from the `/family` endpoint.
+use crate::error::Error::ParseError;
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn from_xml(xml: &str) -> error::Result<Vec<Family>> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Family XML: {}", xml);
+
+    xml::check_errors("family", xml)?;
+
+    let mut families = Vec::new();
+    for item in xml::from_str::<ItemsXml>("family", xml)?.inner {
+        families.push(item.try_into()?);
+    }
+    Ok(families)
+}
+
+/// Represents a family, e.g. "Game: Catan".
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Family {
+    pub id: u32,
+    pub name: String,
+    pub description: Option<String>,
+    pub thumbnail: Option<String>,
+    pub image: Option<String>,
+    pub members: Vec<FamilyMember>,
+}
+
+/// A thing that is a member of a [`Family`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FamilyMember {
+    #[serde(rename = "@id")]
+    pub id: u32,
+    #[serde(rename = "@value")]
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct ItemsXml {
+    #[serde(rename = "item", default)]
+    inner: Vec<ItemXml>,
+}
+
+#[derive(Deserialize)]
+struct ItemXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "name")]
+    names: Vec<NameXml>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    description: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    thumbnail: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    image: Option<String>,
+    #[serde(rename = "link", default)]
+    links: Vec<FamilyMember>,
+}
+
+// example: <name type="primary" sortindex="1" value="Game: Catan"/>
+#[derive(Deserialize)]
+struct NameXml {
+    #[serde(rename = "@type")]
+    r#type: String,
+    #[serde(rename = "@value")]
+    value: String,
+}
+
+impl TryFrom<ItemXml> for Family {
+    type Error = error::Error;
+
+    fn try_from(item: ItemXml) -> Result<Self, error::Error> {
+        let name = if let Some(name) = item.names.into_iter().find(|n| n.r#type == "primary") {
+            name.value
+        } else {
+            return Err(ParseError {
+                endpoint: "family",
+                item_id: Some(item.id),
+                source: "No primary name found".into(),
+            });
+        };
+
+        Ok(Self {
+            id: item.id,
+            name,
+            description: item.description,
+            thumbnail: item.thumbnail,
+            image: item.image,
+            members: item.links,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::family::from_xml;
+    use std::fs;
+
+    #[test]
+    fn test_from_xml() {
+        let families =
+            from_xml(&fs::read_to_string("test/family-catan.xml").expect("Reading file"))
+                .expect("Parsing XML");
+
+        assert_eq!(families.len(), 2);
+
+        let family = &families[0];
+        assert_eq!(family.id, 3);
+        assert_eq!(family.name, "Game: Catan");
+        assert!(
+            family
+                .description
+                .as_deref()
+                .unwrap()
+                .starts_with("Games in the Catan family.")
+        );
+        assert_eq!(
+            family.thumbnail.as_deref(),
+            Some("https://cf.geekdo-images.com/example__thumb/img/catan-family.jpg")
+        );
+        assert_eq!(family.members.len(), 3);
+        assert_eq!(family.members[0].id, 13);
+        assert_eq!(family.members[0].name, "CATAN");
+
+        let family = &families[1];
+        assert_eq!(family.id, 69);
+        assert_eq!(family.name, "Components: Miniatures");
+        assert_eq!(family.description, None);
+        assert_eq!(family.image, None);
+        assert!(family.members.is_empty());
+    }
+}
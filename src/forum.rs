@@ -0,0 +1,352 @@
+//! The responses from the `/forumlist`, `/forum` and `/thread` endpoints.
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn forum_list_from_xml(xml: &str) -> error::Result<Vec<Forum>> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Forum list XML: {}", xml);
+
+    xml::check_errors("forumlist", xml)?;
+    Ok(xml::from_str::<ForumsXml>("forumlist", xml)?
+        .inner
+        .into_iter()
+        .map(Forum::from)
+        .collect())
+}
+
+pub(super) fn forum_from_xml(xml: &str) -> error::Result<ForumPage> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Forum XML: {}", xml);
+
+    xml::check_errors("forum", xml)?;
+    Ok(xml::from_str::<ForumPageXml>("forum", xml)?.into())
+}
+
+pub(super) fn thread_from_xml(xml: &str) -> error::Result<Thread> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Thread XML: {}", xml);
+
+    xml::check_errors("thread", xml)?;
+    Ok(xml::from_str::<ThreadXml>("thread", xml)?.into())
+}
+
+/// The type of the thing that forums belong to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ForumListType {
+    #[default]
+    Thing,
+    Family,
+}
+
+impl ForumListType {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            ForumListType::Thing => "thing",
+            ForumListType::Family => "family",
+        }
+    }
+}
+
+/// A forum of a thing or family, e.g. "Rules".
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Forum {
+    pub id: u32,
+    pub group_id: u32,
+    pub title: String,
+    pub description: Option<String>,
+    pub no_posting: bool,
+    pub thread_count: u32,
+    pub post_count: u32,
+    pub last_post_date: Option<String>,
+}
+
+/// A page of threads in a [`Forum`]. The XML API returns 50 threads per page.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ForumPage {
+    pub id: u32,
+    pub title: String,
+    pub no_posting: bool,
+    pub thread_count: u32,
+    pub post_count: u32,
+    pub last_post_date: Option<String>,
+    pub threads: Vec<ThreadSummary>,
+}
+
+/// A thread in a [`ForumPage`], without the articles.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ThreadSummary {
+    #[serde(rename = "@id")]
+    pub id: u32,
+    #[serde(rename = "@subject")]
+    pub subject: String,
+    #[serde(rename = "@author")]
+    pub author: String,
+    #[serde(rename = "@numarticles")]
+    pub article_count: u32,
+    #[serde(rename = "@postdate")]
+    pub post_date: String,
+    #[serde(rename = "@lastpostdate")]
+    pub last_post_date: String,
+}
+
+/// A thread and its articles.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Thread {
+    pub id: u32,
+    pub subject: String,
+    pub link: String,
+    pub article_count: u32,
+    pub articles: Vec<Article>,
+}
+
+/// An article, or post, in a [`Thread`].
+///
+/// The body is HTML.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Article {
+    pub id: u32,
+    pub author: String,
+    pub link: String,
+    pub post_date: String,
+    pub edit_date: Option<String>,
+    pub edit_count: u32,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Deserialize)]
+struct ForumsXml {
+    #[serde(rename = "forum", default)]
+    inner: Vec<ForumXml>,
+}
+
+// example: <forum id="26" groupid="0" title="Rules" noposting="0" description="..." numthreads="2054" numposts="11970" lastpostdate="..."/>
+#[derive(Deserialize)]
+struct ForumXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@groupid", default)]
+    group_id: u32,
+    #[serde(rename = "@title")]
+    title: String,
+    #[serde(
+        rename = "@description",
+        default,
+        deserialize_with = "xml::empty_as_none"
+    )]
+    description: Option<String>,
+    #[serde(rename = "@noposting", default)]
+    no_posting: u8,
+    #[serde(rename = "@numthreads", default)]
+    thread_count: u32,
+    #[serde(rename = "@numposts", default)]
+    post_count: u32,
+    #[serde(
+        rename = "@lastpostdate",
+        default,
+        deserialize_with = "xml::empty_as_none"
+    )]
+    last_post_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ForumPageXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@title")]
+    title: String,
+    #[serde(rename = "@noposting", default)]
+    no_posting: u8,
+    #[serde(rename = "@numthreads", default)]
+    thread_count: u32,
+    #[serde(rename = "@numposts", default)]
+    post_count: u32,
+    #[serde(
+        rename = "@lastpostdate",
+        default,
+        deserialize_with = "xml::empty_as_none"
+    )]
+    last_post_date: Option<String>,
+    threads: Option<ThreadsXml>,
+}
+
+#[derive(Deserialize)]
+struct ThreadsXml {
+    #[serde(rename = "thread", default)]
+    inner: Vec<ThreadSummary>,
+}
+
+#[derive(Deserialize)]
+struct ThreadXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@numarticles", default)]
+    article_count: u32,
+    #[serde(rename = "@link", default)]
+    link: String,
+    #[serde(default)]
+    subject: String,
+    articles: Option<ArticlesXml>,
+}
+
+#[derive(Deserialize)]
+struct ArticlesXml {
+    #[serde(rename = "article", default)]
+    inner: Vec<ArticleXml>,
+}
+
+#[derive(Deserialize)]
+struct ArticleXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@username")]
+    username: String,
+    #[serde(rename = "@link", default)]
+    link: String,
+    #[serde(rename = "@postdate")]
+    post_date: String,
+    #[serde(rename = "@editdate", default, deserialize_with = "xml::empty_as_none")]
+    edit_date: Option<String>,
+    #[serde(rename = "@numedits", default)]
+    edit_count: u32,
+    #[serde(default)]
+    subject: String,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+impl From<ForumXml> for Forum {
+    fn from(forum: ForumXml) -> Self {
+        Self {
+            id: forum.id,
+            group_id: forum.group_id,
+            title: forum.title,
+            description: forum.description,
+            no_posting: forum.no_posting != 0,
+            thread_count: forum.thread_count,
+            post_count: forum.post_count,
+            last_post_date: forum.last_post_date,
+        }
+    }
+}
+
+impl From<ForumPageXml> for ForumPage {
+    fn from(forum: ForumPageXml) -> Self {
+        Self {
+            id: forum.id,
+            title: forum.title,
+            no_posting: forum.no_posting != 0,
+            thread_count: forum.thread_count,
+            post_count: forum.post_count,
+            last_post_date: forum.last_post_date,
+            threads: forum
+                .threads
+                .map(|threads| threads.inner)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl From<ThreadXml> for Thread {
+    fn from(thread: ThreadXml) -> Self {
+        Self {
+            id: thread.id,
+            subject: thread.subject,
+            link: thread.link,
+            article_count: thread.article_count,
+            articles: thread
+                .articles
+                .map(|articles| articles.inner)
+                .unwrap_or_default()
+                .into_iter()
+                .map(Article::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<ArticleXml> for Article {
+    fn from(article: ArticleXml) -> Self {
+        Self {
+            id: article.id,
+            author: article.username,
+            link: article.link,
+            post_date: article.post_date,
+            edit_date: article.edit_date,
+            edit_count: article.edit_count,
+            subject: article.subject,
+            body: article.body.unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::forum::{forum_from_xml, forum_list_from_xml, thread_from_xml};
+    use std::fs;
+
+    #[test]
+    fn test_forum_list_from_xml() {
+        let forums = forum_list_from_xml(
+            &fs::read_to_string("test/forumlist-catan.xml").expect("Reading file"),
+        )
+        .expect("Parsing XML");
+
+        assert_eq!(forums.len(), 3);
+        assert_eq!(forums[1].id, 26);
+        assert_eq!(forums[1].title, "Rules");
+        assert_eq!(
+            forums[1].description.as_deref(),
+            Some("Post any rules questions you have here.")
+        );
+        assert!(!forums[1].no_posting);
+        assert_eq!(forums[1].thread_count, 2054);
+        assert_eq!(forums[1].post_count, 11970);
+        assert!(forums[2].no_posting);
+        assert_eq!(forums[2].description, None);
+        assert_eq!(forums[2].last_post_date, None);
+    }
+
+    #[test]
+    fn test_forum_from_xml() {
+        let forum =
+            forum_from_xml(&fs::read_to_string("test/forum-rules.xml").expect("Reading file"))
+                .expect("Parsing XML");
+
+        assert_eq!(forum.id, 26);
+        assert_eq!(forum.title, "Rules");
+        assert_eq!(forum.threads.len(), 2);
+        assert_eq!(forum.threads[0].id, 3390123);
+        assert_eq!(forum.threads[0].author, "meeple");
+        assert_eq!(forum.threads[0].article_count, 3);
+        assert_eq!(forum.threads[1].subject, "Trading with the bank & ports");
+    }
+
+    #[test]
+    fn test_thread_from_xml() {
+        let thread =
+            thread_from_xml(&fs::read_to_string("test/thread-robber.xml").expect("Reading file"))
+                .expect("Parsing XML");
+
+        assert_eq!(thread.id, 3390123);
+        assert_eq!(thread.subject, "Robber on a desert hex?");
+        assert_eq!(thread.article_count, 3);
+        assert_eq!(thread.articles.len(), 3);
+
+        let article = &thread.articles[1];
+        assert_eq!(article.id, 44000002);
+        assert_eq!(article.author, "unixgeek");
+        assert_eq!(article.post_date, "2024-12-24T12:00:00-06:00");
+        assert_eq!(
+            article.edit_date.as_deref(),
+            Some("2024-12-24T12:30:00-06:00")
+        );
+        assert_eq!(article.edit_count, 1);
+        assert_eq!(article.subject, "Re: Robber on a desert hex?");
+        assert_eq!(
+            article.body,
+            r#"Yes. See the <b>Almanac</b>, under "Robber"."#
+        );
+        assert_eq!(thread.articles[2].body, "");
+    }
+}
@@ -0,0 +1,205 @@
+//! The response from the `/geeklist` endpoint of the legacy XML API (v1).
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn from_xml(xml: &str) -> error::Result<GeekList> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("GeekList XML: {}", xml);
+
+    xml::check_errors("geeklist", xml)?;
+    Ok(xml::from_str::<GeekListXml>("geeklist", xml)?.into())
+}
+
+/// Represents a GeekList.
+///
+/// `comments` are only included when requested. The description, bodies and comments are
+/// BGG markup.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GeekList {
+    pub id: u32,
+    pub title: String,
+    pub username: String,
+    pub description: String,
+    pub post_date: String,
+    pub edit_date: String,
+    pub thumbs: u32,
+    pub item_count: u32,
+    pub items: Vec<GeekListItem>,
+    pub comments: Vec<GeekListComment>,
+}
+
+/// An item in a [`GeekList`].
+///
+/// `object_type` and `subtype` describe what `object_id` refers to, e.g. a `thing` with a subtype
+/// of `boardgame` can be used with [`crate::BggClient::get_games`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GeekListItem {
+    pub id: u32,
+    pub object_type: String,
+    pub subtype: String,
+    pub object_id: u32,
+    pub object_name: String,
+    pub username: String,
+    pub post_date: String,
+    pub edit_date: String,
+    pub thumbs: u32,
+    pub image_id: Option<u32>,
+    pub body: String,
+    pub comments: Vec<GeekListComment>,
+}
+
+/// A comment on a [`GeekList`] or a [`GeekListItem`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GeekListComment {
+    #[serde(rename = "@username")]
+    pub username: String,
+    #[serde(rename = "@postdate")]
+    pub post_date: String,
+    #[serde(rename = "@editdate")]
+    pub edit_date: String,
+    #[serde(rename = "@thumbs")]
+    pub thumbs: u32,
+    #[serde(rename = "#text", default)]
+    pub text: String,
+}
+
+#[derive(Deserialize)]
+struct GeekListXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "postdate", default)]
+    post_date: String,
+    #[serde(rename = "editdate", default)]
+    edit_date: String,
+    #[serde(default)]
+    thumbs: u32,
+    #[serde(rename = "numitems", default)]
+    item_count: u32,
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(rename = "item", default)]
+    items: Vec<ItemXml>,
+    #[serde(rename = "comment", default)]
+    comments: Vec<GeekListComment>,
+}
+
+// example: <item id="9000001" objecttype="thing" subtype="boardgame" objectid="246900" objectname="..." username="..." postdate="..." editdate="..." thumbs="5" imageid="5235277">
+#[derive(Deserialize)]
+struct ItemXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@objecttype")]
+    object_type: String,
+    #[serde(rename = "@subtype", default)]
+    subtype: String,
+    #[serde(rename = "@objectid")]
+    object_id: u32,
+    #[serde(rename = "@objectname")]
+    object_name: String,
+    #[serde(rename = "@username")]
+    username: String,
+    #[serde(rename = "@postdate")]
+    post_date: String,
+    #[serde(rename = "@editdate", default)]
+    edit_date: String,
+    #[serde(rename = "@thumbs", default)]
+    thumbs: u32,
+    #[serde(rename = "@imageid", default)]
+    image_id: u32,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(rename = "comment", default)]
+    comments: Vec<GeekListComment>,
+}
+
+impl From<GeekListXml> for GeekList {
+    fn from(geeklist: GeekListXml) -> Self {
+        Self {
+            id: geeklist.id,
+            title: geeklist.title,
+            username: geeklist.username,
+            description: geeklist.description.unwrap_or_default(),
+            post_date: geeklist.post_date,
+            edit_date: geeklist.edit_date,
+            thumbs: geeklist.thumbs,
+            item_count: geeklist.item_count,
+            items: geeklist.items.into_iter().map(GeekListItem::from).collect(),
+            comments: geeklist.comments,
+        }
+    }
+}
+
+impl From<ItemXml> for GeekListItem {
+    fn from(item: ItemXml) -> Self {
+        Self {
+            id: item.id,
+            object_type: item.object_type,
+            subtype: item.subtype,
+            object_id: item.object_id,
+            object_name: item.object_name,
+            username: item.username,
+            post_date: item.post_date,
+            edit_date: item.edit_date,
+            thumbs: item.thumbs,
+            // 0 means there is no image.
+            image_id: Some(item.image_id).filter(|id| *id != 0),
+            body: item.body.unwrap_or_default(),
+            comments: item.comments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geeklist::from_xml;
+    use std::fs;
+
+    #[test]
+    fn test_from_xml() {
+        let geeklist = from_xml(&fs::read_to_string("test/geeklist.xml").expect("Reading file"))
+            .expect("Parsing XML");
+
+        assert_eq!(geeklist.id, 123456);
+        assert_eq!(geeklist.title, "Best 2-player games");
+        assert_eq!(geeklist.username, "unixgeek");
+        assert_eq!(
+            geeklist.description,
+            "Games that shine with <b>two</b> players."
+        );
+        assert_eq!(geeklist.post_date, "Fri, 03 Jan 2025 17:20:00 +0000");
+        assert_eq!(geeklist.thumbs, 42);
+        assert_eq!(geeklist.item_count, 2);
+        assert_eq!(geeklist.comments.len(), 1);
+        assert_eq!(geeklist.comments[0].username, "meeple");
+        assert_eq!(geeklist.comments[0].thumbs, 2);
+        assert_eq!(geeklist.comments[0].text, "Great list!");
+
+        assert_eq!(geeklist.items.len(), 2);
+        let item = &geeklist.items[0];
+        assert_eq!(item.id, 9000001);
+        assert_eq!(item.object_type, "thing");
+        assert_eq!(item.subtype, "boardgame");
+        assert_eq!(item.object_id, 246900);
+        assert_eq!(item.object_name, "Eclipse: Second Dawn for the Galaxy");
+        assert_eq!(item.username, "unixgeek");
+        assert_eq!(item.thumbs, 5);
+        assert_eq!(item.image_id, Some(5235277));
+        assert_eq!(item.body, "Surprisingly good head to head.");
+        assert_eq!(item.comments.len(), 2);
+        assert_eq!(
+            item.comments[0].edit_date,
+            "Fri, 03 Jan 2025 18:05:00 +0000"
+        );
+        assert_eq!(item.comments[1].text, "Best at 4, but still good at 2.");
+
+        let item = &geeklist.items[1];
+        assert_eq!(item.object_name, "1830: Railways & Robber Barons");
+        assert_eq!(item.image_id, None);
+        assert_eq!(item.body, "");
+        assert!(item.comments.is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+//! The response from the `/guild` endpoint.
+use crate::error::Error::{NotFound, XmlApiError};
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+// The XML API returns 25 members per page.
+pub(super) const MEMBERS_PER_PAGE: u32 = 25;
+
+pub(super) fn from_xml(xml: &str) -> error::Result<Guild> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Guild XML: {}", xml);
+
+    xml::check_errors("guild", xml)?;
+    xml::from_str::<GuildXml>("guild", xml)?.try_into()
+}
+
+/// Represents a guild.
+///
+/// `member_count` and `members` are only `Some` when members are requested.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Guild {
+    pub id: u32,
+    pub name: String,
+    pub created: String,
+    pub category: Option<String>,
+    pub website: Option<String>,
+    pub manager: Option<String>,
+    pub description: Option<String>,
+    pub location: Location,
+    pub member_count: Option<u32>,
+    pub members: Option<Vec<GuildMember>>,
+}
+
+/// The location of a [`Guild`]. Empty values are `None`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Location {
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    pub addr1: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    pub addr2: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    pub city: Option<String>,
+    #[serde(
+        rename = "stateorprovince",
+        default,
+        deserialize_with = "xml::empty_as_none"
+    )]
+    pub state_or_province: Option<String>,
+    #[serde(
+        rename = "postalcode",
+        default,
+        deserialize_with = "xml::empty_as_none"
+    )]
+    pub postal_code: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    pub country: Option<String>,
+}
+
+/// A member of a [`Guild`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GuildMember {
+    #[serde(rename = "@name")]
+    pub name: String,
+    /// The date the member joined.
+    #[serde(rename = "@date")]
+    pub joined: String,
+}
+
+#[derive(Deserialize)]
+struct GuildXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@name", default)]
+    name: String,
+    #[serde(rename = "@created", default)]
+    created: String,
+    // The only child element if the guild does not exist.
+    error: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    category: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    website: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    manager: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    description: Option<String>,
+    #[serde(default)]
+    location: Location,
+    members: Option<MembersXml>,
+}
+
+#[derive(Deserialize)]
+struct MembersXml {
+    #[serde(rename = "@count")]
+    count: u32,
+    #[serde(rename = "member", default)]
+    inner: Vec<GuildMember>,
+}
+
+impl TryFrom<GuildXml> for Guild {
+    type Error = error::Error;
+
+    fn try_from(guild: GuildXml) -> Result<Self, error::Error> {
+        if let Some(message) = guild.error {
+            return Err(if message.to_lowercase().contains("not found") {
+                NotFound
+            } else {
+                XmlApiError(message)
+            });
+        }
+
+        let (member_count, members) = guild
+            .members
+            .map(|members| (members.count, members.inner))
+            .unzip();
+
+        Ok(Self {
+            id: guild.id,
+            name: guild.name,
+            created: guild.created,
+            category: guild.category,
+            website: guild.website,
+            manager: guild.manager,
+            description: guild.description,
+            location: guild.location,
+            member_count,
+            members,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::NotFound;
+    use crate::guild::{MEMBERS_PER_PAGE, from_xml};
+    use std::fs;
+
+    #[test]
+    fn test_from_xml() {
+        let guild = from_xml(&fs::read_to_string("test/guild-page-1.xml").expect("Reading file"))
+            .expect("Parsing XML");
+
+        assert_eq!(guild.id, 1299);
+        assert_eq!(guild.name, "Richmond Board Gamers");
+        assert_eq!(guild.created, "Mon, 16 Jun 2008 14:32:43 +0000");
+        assert_eq!(guild.category.as_deref(), Some("group"));
+        assert_eq!(guild.website.as_deref(), Some("https://example.com/rbg"));
+        assert_eq!(guild.manager.as_deref(), Some("unixgeek"));
+        assert_eq!(guild.location.addr1, None);
+        assert_eq!(guild.location.city.as_deref(), Some("Richmond"));
+        assert_eq!(
+            guild.location.state_or_province.as_deref(),
+            Some("Virginia")
+        );
+        assert_eq!(guild.location.country.as_deref(), Some("United States"));
+        assert_eq!(guild.member_count, Some(27));
+
+        let members = guild.members.unwrap();
+        assert_eq!(members.len(), MEMBERS_PER_PAGE as usize);
+        assert_eq!(members[0].name, "member01");
+        assert_eq!(members[0].joined, "Sat, 23 Oct 2010 11:57:37 +0000");
+    }
+
+    #[test]
+    fn test_from_xml_last_page() {
+        let guild = from_xml(&fs::read_to_string("test/guild-page-2.xml").expect("Reading file"))
+            .expect("Parsing XML");
+
+        assert_eq!(guild.member_count, Some(27));
+        let members = guild.members.unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[1].name, "unixgeek");
+    }
+
+    #[test]
+    fn test_from_xml_not_found() {
+        let result =
+            from_xml(&fs::read_to_string("test/guild-not-found.xml").expect("Reading file"));
+
+        assert!(matches!(result, Err(NotFound)));
+    }
+}
@@ -0,0 +1,126 @@
+//! The response from the `/hot` endpoint.
+use crate::xml::ValueAttribute;
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn from_xml(xml: &str) -> error::Result<Vec<HotItem>> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Hot XML: {}", xml);
+
+    xml::check_errors("hot", xml)?;
+    Ok(xml::from_str::<ItemsXml>("hot", xml)?
+        .inner
+        .into_iter()
+        .map(HotItem::from)
+        .collect())
+}
+
+/// The type of items in the hot list.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HotType {
+    #[default]
+    BoardGame,
+    Rpg,
+    VideoGame,
+    BoardGamePerson,
+    RpgPerson,
+    BoardGameCompany,
+    RpgCompany,
+    VideoGameCompany,
+}
+
+impl HotType {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            HotType::BoardGame => "boardgame",
+            HotType::Rpg => "rpg",
+            HotType::VideoGame => "videogame",
+            HotType::BoardGamePerson => "boardgameperson",
+            HotType::RpgPerson => "rpgperson",
+            HotType::BoardGameCompany => "boardgamecompany",
+            HotType::RpgCompany => "rpgcompany",
+            HotType::VideoGameCompany => "videogamecompany",
+        }
+    }
+}
+
+/// An item in the hot list.
+///
+/// People and companies do not have a year.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HotItem {
+    pub rank: u32,
+    pub id: u32,
+    pub name: String,
+    pub year_published: Option<i32>,
+    pub thumbnail: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ItemsXml {
+    #[serde(rename = "item", default)]
+    inner: Vec<ItemXml>,
+}
+
+#[derive(Deserialize)]
+struct ItemXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@rank")]
+    rank: u32,
+    name: ValueAttribute,
+    #[serde(rename = "yearpublished")]
+    year_published: Option<ValueAttribute>,
+    thumbnail: Option<ValueAttribute>,
+}
+
+impl From<ItemXml> for HotItem {
+    fn from(item: ItemXml) -> Self {
+        Self {
+            rank: item.rank,
+            id: item.id,
+            name: item.name.value,
+            year_published: item
+                .year_published
+                .and_then(ValueAttribute::into_option)
+                .and_then(|year| year.parse().ok()),
+            thumbnail: item.thumbnail.and_then(ValueAttribute::into_option),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hot::from_xml;
+    use std::fs;
+
+    #[test]
+    fn test_from_xml() {
+        let items = from_xml(&fs::read_to_string("test/hot-boardgame.xml").expect("Reading file"))
+            .expect("Parsing XML");
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].rank, 1);
+        assert_eq!(items[0].id, 430350);
+        assert_eq!(items[0].name, "Enormity");
+        assert_eq!(items[0].year_published, Some(2025));
+        assert_eq!(
+            items[0].thumbnail.as_deref(),
+            Some("https://cf.geekdo-images.com/example__thumb/img/enormity.jpg")
+        );
+        assert_eq!(items[2].rank, 3);
+        assert_eq!(items[2].name, "1830: Railways & Robber Barons");
+        assert_eq!(items[2].thumbnail, None);
+    }
+
+    #[test]
+    fn test_from_xml_person() {
+        let items =
+            from_xml(&fs::read_to_string("test/hot-boardgameperson.xml").expect("Reading file"))
+                .expect("Parsing XML");
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, 11825);
+        assert_eq!(items[0].year_published, None);
+    }
+}
@@ -0,0 +1,55 @@
+//! Iterators that request games lazily, a batch at a time.
+use crate::options::FetchOptions;
+use crate::{BggClient, Game, MAX_IDS, error};
+use std::vec;
+
+/// An iterator over games, that requests the next batch of games when the previous batch is used
+/// up.
+///
+/// Yields an error if a batch can't be got, and nothing after that. Stop iterating to stop
+/// requesting games. See [`BggClient::get_games_iter`].
+pub struct GamesIter<'a> {
+    client: &'a BggClient,
+    ids: Vec<u32>,
+    next_id: usize,
+    batch: vec::IntoIter<Game>,
+    failed: bool,
+}
+
+impl<'a> GamesIter<'a> {
+    pub(super) fn new(client: &'a BggClient, ids: Vec<u32>) -> Self {
+        Self {
+            client,
+            ids,
+            next_id: 0,
+            batch: Vec::new().into_iter(),
+            failed: false,
+        }
+    }
+}
+
+impl Iterator for GamesIter<'_> {
+    type Item = error::Result<Game>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(game) = self.batch.next() {
+                return Some(Ok(game));
+            }
+            if self.failed || self.next_id >= self.ids.len() {
+                return None;
+            }
+
+            let end = (self.next_id + MAX_IDS as usize).min(self.ids.len());
+            let ids = &self.ids[self.next_id..end];
+            self.next_id = end;
+            match self.client.games(ids, &FetchOptions::default()) {
+                Ok(games) => self.batch = games.into_iter(),
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
@@ -11,26 +11,116 @@
 //!     # Ok(())
 //! # }
 //! ```
+//!
+//! # Features
+//! * `async`: [`AsyncBggClient`][async], a non-blocking version of [`BggClient`] using `reqwest`
+//!   and `tokio`.
+//! * `moar-debug`: More debug logging, to help understand the XML API.
+//! * `testing`: [`testing::MockServer`][mock], a mock of the XML API to test code using
+//!   [`BggClient`] offline, and [`testing::RecordingTransport`][record] and
+//!   [`testing::ReplayTransport`][replay] to record real responses and replay them.
+//!
+//! The links only go to the items when their feature is enabled, e.g. on docs.rs.
+//!
+#![cfg_attr(feature = "async", doc = "[async]: crate::AsyncBggClient")]
+#![cfg_attr(not(feature = "async"), doc = "[async]: #features")]
+#![cfg_attr(feature = "testing", doc = "[mock]: crate::testing::MockServer")]
+#![cfg_attr(
+    feature = "testing",
+    doc = "[record]: crate::testing::RecordingTransport"
+)]
+#![cfg_attr(feature = "testing", doc = "[replay]: crate::testing::ReplayTransport")]
+#![cfg_attr(not(feature = "testing"), doc = "[mock]: #features")]
+#![cfg_attr(not(feature = "testing"), doc = "[record]: #features")]
+#![cfg_attr(not(feature = "testing"), doc = "[replay]: #features")]
+#[cfg(feature = "async")]
+mod async_client;
+mod builder;
+mod cache;
+mod cancel;
 mod collection;
 pub mod error;
+mod family;
+mod forum;
+mod geeklist;
+mod guild;
+mod hot;
+mod iter;
+mod library;
+mod options;
+mod person;
+mod progress;
+mod rate_limit;
 mod request;
+mod retry;
+mod search;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 mod thing;
+mod transport;
+mod user;
+mod xml;
 
+#[cfg(feature = "async")]
+pub use crate::async_client::AsyncBggClient;
+pub use crate::builder::BggClientBuilder;
+pub use crate::cache::{Cache, CacheTtls, FileCache, GameCache, MemoryCache, MemoryGameCache};
+pub use crate::cancel::CancellationToken;
 pub use crate::collection::Item as CollectionItem;
-use crate::request::RequestResult;
-pub use crate::thing::Game;
+pub use crate::family::{Family, FamilyMember};
+pub use crate::forum::{Article, Forum, ForumListType, ForumPage, Thread, ThreadSummary};
+pub use crate::geeklist::{GeekList, GeekListComment, GeekListItem};
+pub use crate::guild::{Guild, GuildMember, Location};
+pub use crate::hot::{HotItem, HotType};
+pub use crate::iter::GamesIter;
+pub use crate::library::{Library, LibraryGame};
+pub use crate::options::FetchOptions;
+pub use crate::person::{Company, Person, PersonRole};
+pub use crate::progress::ProgressObserver;
+pub use crate::rate_limit::RateLimiter;
+pub use crate::retry::{RetryBehavior, RetryPolicy};
+pub use crate::search::{SearchResult, SearchType};
+pub use crate::thing::{FailedGames, Game, PartialGames};
+pub use crate::transport::{HttpRequest, HttpResponse, Transport, UreqTransport};
+pub use crate::user::{Buddy, Domain, RankedItem, User, UserGuild, UserOptions, UserPage};
 use log::debug;
-use ureq::Agent;
-use ureq::http::{HeaderMap, HeaderValue, StatusCode};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
 
 // bgg says max is 20.
 const MAX_IDS: u8 = 20;
 
+fn user_agent() -> String {
+    format!(
+        "{} {} {}",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        env!("CARGO_PKG_REPOSITORY")
+    )
+}
+
 /// Client for the [BGG XML API2](https://boardgamegeek.com/wiki/page/BGG_XML_API2)
+///
+/// A few things that are missing from XML API2 use the
+/// [legacy XML API](https://boardgamegeek.com/wiki/page/BGG_XML_API).
+///
+/// Cloning is cheap, and clones share the same [`Transport`], [`RateLimiter`] and [`Cache`].
+#[derive(Clone)]
 pub struct BggClient {
-    agent: Agent,
+    transport: Arc<dyn Transport>,
     url: String,
-    api_token: String,
+    api_token: Option<String>,
+    // Sent with every request, in addition to the Authorization header.
+    headers: Vec<(String, String)>,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cache: Option<Arc<dyn Cache>>,
+    cache_ttls: CacheTtls,
+    game_cache: Option<Arc<dyn GameCache>>,
+    concurrency: usize,
 }
 
 impl BggClient {
@@ -42,20 +132,23 @@ impl BggClient {
     /// * <https://rpggeek.com>
     /// * <https://videogamegeek.com>
     pub fn from_url(url: &str, api_token: &str) -> Self {
-        let agent = Agent::config_builder()
-            .http_status_as_error(false)
-            .user_agent(format!(
-                "{} {} {}",
-                env!("CARGO_PKG_NAME"),
-                env!("CARGO_PKG_VERSION"),
-                env!("CARGO_PKG_REPOSITORY")
-            ))
-            .build()
-            .into();
+        Self::with_transport(url, api_token, UreqTransport::default())
+    }
+
+    /// Creates a [`BggClient`] with the specified URL as the base, that sends requests with
+    /// `transport` instead of [`UreqTransport`].
+    pub fn with_transport(url: &str, api_token: &str, transport: impl Transport + 'static) -> Self {
         Self {
-            agent,
+            transport: Arc::new(transport),
             url: url.to_owned(),
-            api_token: api_token.to_owned(),
+            api_token: Some(api_token.to_owned()),
+            headers: Vec::new(),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: None,
+            cache: None,
+            cache_ttls: CacheTtls::default(),
+            game_cache: None,
+            concurrency: 1,
         }
     }
 
@@ -64,6 +157,78 @@ impl BggClient {
         Self::from_url("https://boardgamegeek.com", api_token)
     }
 
+    /// Creates a [`BggClient`] that sends requests without an API token.
+    ///
+    /// This is only useful for endpoints that don't need a token. The others return
+    /// [`error::Error::Unauthorized`]. Use [`Self::without_api_token`] for other URLs or
+    /// transports.
+    pub fn without_token() -> Self {
+        Self::new("").without_api_token()
+    }
+
+    /// Sends requests without an API token. See [`Self::without_token`].
+    pub fn without_api_token(mut self) -> Self {
+        self.api_token = None;
+        self
+    }
+
+    /// Returns a [`BggClientBuilder`], to configure the HTTP connection.
+    pub fn builder() -> BggClientBuilder {
+        BggClientBuilder::new()
+    }
+
+    /// Checks that the API token is accepted by the XML API, by requesting a single thing.
+    ///
+    /// Returns [`error::Error::Unauthorized`] if the token is missing or invalid, and
+    /// [`error::Error::Forbidden`] if it is not allowed to use the XML API.
+    pub fn validate_token(&self) -> error::Result<()> {
+        // Any thing will do. This is CATAN.
+        self.fetch(&thing_url(&self.url, &[13]), |_| Ok(()))
+    }
+
+    /// Uses `retry_policy` instead of the default [`RetryPolicy`].
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Waits for `rate_limiter` before every request, including retries. There is no rate
+    /// limiting by default.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Caches the responses of [`Self::get_collection`] and [`Self::get_games`] in `cache`, for
+    /// the default [`CacheTtls`]. There is no caching by default.
+    pub fn with_cache(mut self, cache: impl Cache + 'static) -> Self {
+        self.cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Caches games by id in `game_cache`, so [`Self::get_games`] only requests games that are
+    /// missing or older than [`CacheTtls::thing`].
+    pub fn with_game_cache(mut self, game_cache: impl GameCache + 'static) -> Self {
+        self.game_cache = Some(Arc::new(game_cache));
+        self
+    }
+
+    /// Requests up to `concurrency` batches of games at once in [`Self::get_games`], instead of
+    /// one at a time. The games are still returned in the same order.
+    ///
+    /// Every request still waits for the [`RateLimiter`], if any, and is retried according to the
+    /// [`RetryPolicy`], so consider a rate limiter to avoid being throttled.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Caches responses for `cache_ttls` instead of the default [`CacheTtls`].
+    pub fn with_cache_ttls(mut self, cache_ttls: CacheTtls) -> Self {
+        self.cache_ttls = cache_ttls;
+        self
+    }
+
     /// Get a user's collection.
     ///
     /// Calls `/collection` with `brief=1` and `subtype=boardgame`.
@@ -72,34 +237,21 @@ impl BggClient {
         user: &str,
         include_expansions: bool,
     ) -> error::Result<Vec<CollectionItem>> {
-        let exclude_param = if include_expansions {
-            ""
-        } else {
-            "&excludesubtype=boardgameexpansion"
-        };
-
-        let url = format!(
-            "{base}/xmlapi2/collection?username={user}&own=1&brief=1&subtype=boardgame{exclude_param}",
-            base = self.url
-        );
+        self.get_collection_with(user, include_expansions, &FetchOptions::default())
+    }
 
-        request::do_request(|| {
-            let mut response = self
-                .agent
-                .get(&url)
-                .header("Authorization", format!("Bearer {}", self.api_token))
-                .call()?;
-            log_headers(response.headers());
-
-            let status_code = response.status();
-            match status_code {
-                StatusCode::OK => {
-                    let xml = response.body_mut().read_to_string()?;
-                    Ok(RequestResult::Done(collection::from_xml(&xml)))
-                }
-                _ => Ok(RequestResult::NotDone(status_code)),
-            }
-        })?
+    /// Get a user's collection, with [`FetchOptions`].
+    ///
+    /// See [`Self::get_collection`].
+    pub fn get_collection_with(
+        &self,
+        user: &str,
+        include_expansions: bool,
+        options: &FetchOptions,
+    ) -> error::Result<Vec<CollectionItem>> {
+        let items = self.collection_items(user, include_expansions, options)?;
+        options.notify(|progress| progress.done());
+        Ok(items)
     }
 
     /// Get games.
@@ -107,20 +259,54 @@ impl BggClient {
     /// Calls `/thing`.
     /// Note that [`Self::get_collection`] is limited to the `boardgame` subtype, but this is not.
     /// `Thing`s that are not boardgames have not been tested.
+    ///
+    /// With a [`GameCache`], only the games that are not cached are requested, and the games are
+    /// returned in the order of `ids`.
     pub fn get_games(&self, ids: &[u32]) -> error::Result<Vec<Game>> {
-        let mut games = Vec::new();
-        let total = ids.len();
-        let mut count = 0;
-
-        for chunk in ids.chunks(MAX_IDS as usize) {
-            count += chunk.len();
-            debug!("Getting games ({count} / {total})");
-            games.extend(self.get_games_from_api(chunk)?);
-        }
+        self.get_games_with(ids, &FetchOptions::default())
+    }
 
+    /// Get games, with [`FetchOptions`].
+    ///
+    /// See [`Self::get_games`].
+    pub fn get_games_with(&self, ids: &[u32], options: &FetchOptions) -> error::Result<Vec<Game>> {
+        let games = self.games(ids, options)?;
+        options.notify(|progress| progress.done());
         Ok(games)
     }
 
+    /// Get games lazily.
+    ///
+    /// Like [`Self::get_games`], but the games are requested a batch at a time, as the iterator is
+    /// used, so they don't all have to be in memory, and stopping early avoids requesting the
+    /// rest. Batches are requested one at a time, regardless of [`Self::with_concurrency`].
+    pub fn get_games_iter(&self, ids: &[u32]) -> GamesIter<'_> {
+        GamesIter::new(self, ids.to_vec())
+    }
+
+    /// Get games, without failing the call if some of them can't be got.
+    ///
+    /// Like [`Self::get_games`], but returns the games that could be got, and the ids that could
+    /// not with their errors. An item that can't be parsed doesn't affect the other items in the
+    /// same response. Only [`error::Error::Cancelled`] and [`error::Error::DeadlineExceeded`] fail
+    /// the call. The games are returned in the order of `ids`.
+    pub fn get_games_partial(&self, ids: &[u32]) -> error::Result<PartialGames> {
+        self.get_games_partial_with(ids, &FetchOptions::default())
+    }
+
+    /// Get games without failing the call, with [`FetchOptions`].
+    ///
+    /// See [`Self::get_games_partial`].
+    pub fn get_games_partial_with(
+        &self,
+        ids: &[u32],
+        options: &FetchOptions,
+    ) -> error::Result<PartialGames> {
+        let partial = self.games_partial(ids, options)?;
+        options.notify(|progress| progress.done());
+        Ok(partial)
+    }
+
     /// Get all games for a user.
     ///
     /// This basically just calls [`Self::get_collection`] and [`Self::get_games`].
@@ -128,53 +314,1216 @@ impl BggClient {
         &self,
         user: &str,
         include_expansions: bool,
+    ) -> error::Result<Vec<Game>> {
+        self.get_all_games_for_user_with(user, include_expansions, &FetchOptions::default())
+    }
+
+    /// Get all games for a user, with [`FetchOptions`].
+    ///
+    /// See [`Self::get_all_games_for_user`].
+    pub fn get_all_games_for_user_with(
+        &self,
+        user: &str,
+        include_expansions: bool,
+        options: &FetchOptions,
     ) -> error::Result<Vec<Game>> {
+        let ids = self
+            .collection_items(user, include_expansions, options)?
+            .into_iter()
+            .map(|item| item.id)
+            .collect::<Vec<u32>>();
+
+        let games = self.games(&ids, options)?;
+        options.notify(|progress| progress.done());
+        Ok(games)
+    }
+
+    /// Get all games for a user lazily.
+    ///
+    /// Gets the user's collection with [`Self::get_collection`], then returns
+    /// [`Self::get_games_iter`] for the games in it.
+    pub fn get_all_games_for_user_iter(
+        &self,
+        user: &str,
+        include_expansions: bool,
+    ) -> error::Result<GamesIter<'_>> {
         let ids = self
             .get_collection(user, include_expansions)?
             .into_iter()
             .map(|item| item.id)
             .collect::<Vec<u32>>();
 
-        self.get_games(&ids)
+        Ok(GamesIter::new(self, ids))
+    }
+
+    /// Get a user.
+    ///
+    /// Calls `/user` without any of the optional lists. See [`Self::get_user_with_options`].
+    pub fn get_user(&self, name: &str) -> error::Result<User> {
+        self.get_user_with_options(name, &UserOptions::default())
+    }
+
+    /// Get a user, including the lists selected in `options`.
+    ///
+    /// Calls `/user`. Returns [`error::Error::InvalidUserError`] if the user does not exist.
+    pub fn get_user_with_options(&self, name: &str, options: &UserOptions) -> error::Result<User> {
+        let url = format!(
+            "{base}/xmlapi2/user?name={name}{query}",
+            base = self.url,
+            name = encode(name),
+            query = options.to_query()
+        );
+
+        self.fetch(&url, user::from_xml)
+    }
+
+    /// Get a guild.
+    ///
+    /// Calls `/guild`. If `include_members` is true, every page of members is requested, so
+    /// large guilds need several calls.
+    pub fn get_guild(&self, id: u32, include_members: bool) -> error::Result<Guild> {
+        let url = format!("{base}/xmlapi2/guild?id={id}", base = self.url);
+        if !include_members {
+            return self.fetch(&url, guild::from_xml);
+        }
+
+        let mut page = 1;
+        let mut guild = self.fetch(&format!("{url}&members=1&page={page}"), guild::from_xml)?;
+        let member_count = guild.member_count.unwrap_or_default() as usize;
+        let mut members = guild.members.take().unwrap_or_default();
+        let mut page_len = members.len();
+
+        // A short page is the last page, in case the member count changes while paging.
+        while members.len() < member_count && page_len == guild::MEMBERS_PER_PAGE as usize {
+            page += 1;
+            debug!("Getting guild members ({} / {member_count})", members.len());
+            let page_members = self
+                .fetch(&format!("{url}&members=1&page={page}"), guild::from_xml)?
+                .members
+                .unwrap_or_default();
+            page_len = page_members.len();
+            members.extend(page_members);
+        }
+
+        guild.members = Some(members);
+        Ok(guild)
+    }
+
+    /// Get the combined games of all members of a guild.
+    ///
+    /// This calls [`Self::get_guild`] and then [`Self::get_library_for_users`] with the members.
+    pub fn get_library_for_guild(
+        &self,
+        id: u32,
+        include_expansions: bool,
+    ) -> error::Result<Library> {
+        let users = self
+            .get_guild(id, true)?
+            .members
+            .unwrap_or_default()
+            .into_iter()
+            .map(|member| member.name)
+            .collect::<Vec<String>>();
+
+        self.get_library_for_users(&users, include_expansions)
+    }
+
+    /// Get the combined games of several users.
+    ///
+    /// Each user's collection is requested with [`Self::get_collection`], then all of the games
+    /// are requested once with [`Self::get_games`]. Users that do not exist are listed in
+    /// [`Library::invalid_users`] instead of failing the whole call.
+    pub fn get_library_for_users<S: AsRef<str>>(
+        &self,
+        users: &[S],
+        include_expansions: bool,
+    ) -> error::Result<Library> {
+        let mut collections = Vec::new();
+        let mut invalid_users = Vec::new();
+
+        for (count, user) in users.iter().map(AsRef::as_ref).enumerate() {
+            debug!("Getting collections ({} / {})", count + 1, users.len());
+            match self.get_collection(user, include_expansions) {
+                Ok(items) => collections.push((user.to_owned(), items)),
+                Err(error::Error::InvalidUserError) => invalid_users.push(user.to_owned()),
+                Err(error) => return Err(error),
+            }
+        }
+
+        let owners_by_id = library::owners_by_id(collections);
+        let ids = owners_by_id.keys().copied().collect::<Vec<u32>>();
+
+        Ok(library::build(
+            self.get_games(&ids)?,
+            owners_by_id,
+            invalid_users,
+        ))
+    }
+
+    /// Get the hot list.
+    ///
+    /// Calls `/hot`.
+    pub fn get_hot(&self, kind: HotType) -> error::Result<Vec<HotItem>> {
+        self.fetch(
+            &format!(
+                "{base}/xmlapi2/hot?type={kind}",
+                base = self.url,
+                kind = kind.as_str()
+            ),
+            hot::from_xml,
+        )
+    }
+
+    /// Search for things by name.
+    ///
+    /// Calls `/search`. If `types` is empty, all types are searched. If `exact` is true, only
+    /// things with a name that exactly matches `query` are returned.
+    pub fn search(
+        &self,
+        query: &str,
+        types: &[SearchType],
+        exact: bool,
+    ) -> error::Result<Vec<SearchResult>> {
+        let type_param = if types.is_empty() {
+            String::new()
+        } else {
+            let types = types
+                .iter()
+                .map(|search_type| search_type.as_str())
+                .collect::<Vec<&str>>()
+                .join(",");
+            format!("&type={types}")
+        };
+        let exact_param = if exact { "&exact=1" } else { "" };
+
+        self.fetch(
+            &format!(
+                "{base}/xmlapi2/search?query={query}{type_param}{exact_param}",
+                base = self.url,
+                query = encode(query)
+            ),
+            search::from_xml,
+        )
+    }
+
+    /// Get the board game that best matches a name.
+    ///
+    /// This calls [`Self::search`] for board games, then [`Self::get_games`] for the best match.
+    /// An exact match of the primary name is preferred, then an exact match of an alternate name,
+    /// then whatever the XML API returned first. Matching ignores case.
+    pub fn resolve_game_by_name(&self, name: &str) -> error::Result<Option<Game>> {
+        let mut results = self.search(name, &[SearchType::BoardGame], false)?;
+        search::rank_by_name(&mut results, name);
+
+        match results.first() {
+            Some(result) => Ok(self.get_games(&[result.id])?.pop()),
+            None => Ok(None),
+        }
+    }
+
+    /// Get families.
+    ///
+    /// Calls `/family`, in batches like [`Self::get_games`].
+    pub fn get_families(&self, ids: &[u32]) -> error::Result<Vec<Family>> {
+        let mut families = Vec::new();
+        let total = ids.len();
+        let mut count = 0;
+
+        for chunk in ids.chunks(MAX_IDS as usize) {
+            count += chunk.len();
+            debug!("Getting families ({count} / {total})");
+            families.extend(self.fetch(
+                &format!(
+                    "{base}/xmlapi2/family?id={ids}",
+                    base = self.url,
+                    ids = join_ids(chunk)
+                ),
+                family::from_xml,
+            )?);
+        }
+
+        Ok(families)
+    }
+
+    /// Get the forums of a thing or family.
+    ///
+    /// Calls `/forumlist`.
+    pub fn get_forum_list(&self, id: u32, list_type: ForumListType) -> error::Result<Vec<Forum>> {
+        self.fetch(
+            &format!(
+                "{base}/xmlapi2/forumlist?id={id}&type={list_type}",
+                base = self.url,
+                list_type = list_type.as_str()
+            ),
+            forum::forum_list_from_xml,
+        )
+    }
+
+    /// Get a page of threads in a forum.
+    ///
+    /// Calls `/forum`. Pages start at 1.
+    pub fn get_forum(&self, id: u32, page: u32) -> error::Result<ForumPage> {
+        self.fetch(
+            &format!("{base}/xmlapi2/forum?id={id}&page={page}", base = self.url),
+            forum::forum_from_xml,
+        )
+    }
+
+    /// Get a thread and its articles.
+    ///
+    /// Calls `/thread`. `min_article_id` and `min_article_date` filter out older articles, and
+    /// `count` limits the number of articles. The date is formatted as `YYYY-MM-DD` or
+    /// `YYYY-MM-DD HH:MM:SS`.
+    pub fn get_thread(
+        &self,
+        id: u32,
+        min_article_id: Option<u32>,
+        min_article_date: Option<&str>,
+        count: Option<u32>,
+    ) -> error::Result<Thread> {
+        let mut url = format!("{base}/xmlapi2/thread?id={id}", base = self.url);
+        if let Some(min_article_id) = min_article_id {
+            url.push_str(&format!("&minarticleid={min_article_id}"));
+        }
+        if let Some(min_article_date) = min_article_date {
+            url.push_str(&format!("&minarticledate={}", encode(min_article_date)));
+        }
+        if let Some(count) = count {
+            url.push_str(&format!("&count={count}"));
+        }
+
+        self.fetch(&url, forum::thread_from_xml)
+    }
+
+    /// Get a GeekList.
+    ///
+    /// Calls `/geeklist` of the legacy XML API (v1), since GeekLists are not part of XML API2.
+    pub fn get_geeklist(&self, id: u32, include_comments: bool) -> error::Result<GeekList> {
+        let comments_param = if include_comments { "?comments=1" } else { "" };
+
+        self.fetch(
+            &format!(
+                "{base}/xmlapi/geeklist/{id}{comments_param}",
+                base = self.url
+            ),
+            geeklist::from_xml,
+        )
+    }
+
+    /// Get a person, e.g. a designer or an artist.
+    ///
+    /// Calls `/boardgamedesigner` or `/boardgameartist` of the legacy XML API (v1), since people
+    /// are not part of XML API2. The ids are the same as in the `boardgamedesigner` and
+    /// `boardgameartist` links of a `/thing`.
+    pub fn get_person(&self, id: u32, role: PersonRole) -> error::Result<Person> {
+        self.fetch(
+            &format!(
+                "{base}/xmlapi/{role}/{id}",
+                base = self.url,
+                role = role.as_str()
+            ),
+            |xml| person::person_from_xml(xml, id, role),
+        )
+    }
+
+    /// Get a company, e.g. a publisher.
+    ///
+    /// Calls `/boardgamepublisher` of the legacy XML API (v1), since companies are not part of
+    /// XML API2.
+    pub fn get_company(&self, id: u32) -> error::Result<Company> {
+        self.fetch(
+            &format!("{base}/xmlapi/boardgamepublisher/{id}", base = self.url),
+            |xml| person::company_from_xml(xml, id),
+        )
+    }
+
+    fn collection_items(
+        &self,
+        user: &str,
+        include_expansions: bool,
+        options: &FetchOptions,
+    ) -> error::Result<Vec<CollectionItem>> {
+        self.fetch_cached(
+            &collection_url(&self.url, user, include_expansions),
+            self.cache_ttls.collection,
+            options,
+            collection::from_xml,
+        )
+    }
+
+    /// Gets games from the [`GameCache`], if any, and requests the rest.
+    fn games(&self, ids: &[u32], options: &FetchOptions) -> error::Result<Vec<Game>> {
+        let Some(game_cache) = &self.game_cache else {
+            return self.fetch_games(ids, options);
+        };
+
+        let (mut games_by_id, missing_ids) = self.cached_games(ids);
+        for game in self.fetch_games(&missing_ids, options)? {
+            game_cache.put(&game);
+            games_by_id.insert(game.id, game);
+        }
+
+        Ok(ids
+            .iter()
+            .filter_map(|id| games_by_id.get(id).cloned())
+            .collect())
+    }
+
+    /// Like [`Self::games`], but failures don't fail the call.
+    fn games_partial(&self, ids: &[u32], options: &FetchOptions) -> error::Result<PartialGames> {
+        let (mut games_by_id, missing_ids) = self.cached_games(ids);
+        let mut failed = Vec::new();
+
+        let results = self.fetch_batches(&missing_ids, options, false, |chunk| {
+            self.fetch_cached(
+                &thing_url(&self.url, chunk),
+                self.cache_ttls.thing,
+                options,
+                thing::from_xml_partial,
+            )
+        });
+        for (chunk, result) in results {
+            match result {
+                Ok(partial) => {
+                    for game in partial.games {
+                        if let Some(game_cache) = &self.game_cache {
+                            game_cache.put(&game);
+                        }
+                        games_by_id.insert(game.id, game);
+                    }
+                    failed.extend(partial.failed);
+                }
+                // The rest of the batches failed the same way, so don't pretend otherwise.
+                Err(error @ (error::Error::Cancelled | error::Error::DeadlineExceeded)) => {
+                    return Err(error);
+                }
+                Err(error) => failed.push(FailedGames {
+                    ids: chunk.to_vec(),
+                    error,
+                }),
+            }
+        }
+
+        Ok(PartialGames {
+            games: ids
+                .iter()
+                .filter_map(|id| games_by_id.get(id).cloned())
+                .collect(),
+            failed,
+        })
+    }
+
+    /// Returns the games in the [`GameCache`], if any, and the ids of the games that are not.
+    fn cached_games(&self, ids: &[u32]) -> (HashMap<u32, Game>, Vec<u32>) {
+        let mut games_by_id = HashMap::new();
+        let mut missing_ids = Vec::new();
+        let mut seen_ids = HashSet::new();
+        for &id in ids {
+            if !seen_ids.insert(id) {
+                continue;
+            }
+            let game = self
+                .game_cache
+                .as_ref()
+                .and_then(|game_cache| game_cache.get(id, self.cache_ttls.thing));
+            match game {
+                Some(game) => {
+                    games_by_id.insert(id, game);
+                }
+                None => missing_ids.push(id),
+            }
+        }
+        debug!(
+            "Using {} cached games, getting {}",
+            games_by_id.len(),
+            missing_ids.len()
+        );
+
+        (games_by_id, missing_ids)
+    }
+
+    /// Requests games in batches, failing on the first error.
+    fn fetch_games(&self, ids: &[u32], options: &FetchOptions) -> error::Result<Vec<Game>> {
+        let mut games = Vec::new();
+        for (_, result) in self.fetch_batches(ids, options, true, |chunk| {
+            self.get_games_from_api(chunk, options)
+        }) {
+            games.extend(result?);
+        }
+
+        Ok(games)
     }
 
-    fn get_games_from_api(&self, ids: &[u32]) -> error::Result<Vec<Game>> {
-        let ids_as_strings = ids.iter().map(ToString::to_string).collect::<Vec<String>>();
+    /// Calls `fetch` with batches of [`MAX_IDS`] ids, `concurrency` batches at a time, and returns
+    /// the results in the order of the batches.
+    ///
+    /// If `stop_on_error` is true, no more batches are started after an error, so the results end
+    /// with the first error.
+    fn fetch_batches<'a, T: Send>(
+        &self,
+        ids: &'a [u32],
+        options: &FetchOptions,
+        stop_on_error: bool,
+        fetch: impl Fn(&[u32]) -> error::Result<T> + Sync,
+    ) -> Vec<(&'a [u32], error::Result<T>)> {
+        let chunks = ids.chunks(MAX_IDS as usize).collect::<Vec<&[u32]>>();
+        let fetch_chunk = |index: usize| {
+            let chunk = chunks[index];
+            options.notify(|progress| progress.batch_started(index + 1, chunks.len(), chunk));
+            let result = fetch(chunk);
+            if result.is_ok() {
+                options.notify(|progress| progress.batch_finished(index + 1, chunks.len()));
+            }
+            (chunk, result)
+        };
+
+        let workers = self.concurrency.min(chunks.len());
+        if workers <= 1 {
+            let mut results = Vec::new();
+            let total = ids.len();
+            let mut count = 0;
 
-        request::do_request(|| {
-            let ids_string = ids_as_strings.join(",");
-            let mut response = self
-                .agent
-                .get(&format!(
-                    "{base}/xmlapi2/thing?id={ids_string}&stats=1",
-                    base = self.url
-                ))
-                .header("Authorization", format!("Bearer {}", self.api_token))
-                .call()?;
+            for (index, chunk) in chunks.iter().enumerate() {
+                count += chunk.len();
+                debug!("Getting games ({count} / {total})");
+                let (chunk, result) = fetch_chunk(index);
+                let stop = stop_on_error && result.is_err();
+                results.push((chunk, result));
+                if stop {
+                    break;
+                }
+            }
+
+            return results;
+        }
+
+        let next_chunk = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let mut results = thread::scope(|scope| {
+            let handles = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        // Stop taking chunks after an error, since the call will fail anyway.
+                        while !failed.load(Ordering::Relaxed) {
+                            let index = next_chunk.fetch_add(1, Ordering::Relaxed);
+                            if index >= chunks.len() {
+                                break;
+                            }
+                            debug!("Getting games (batch {} / {})", index + 1, chunks.len());
+                            let (chunk, result) = fetch_chunk(index);
+                            if stop_on_error && result.is_err() {
+                                failed.store(true, Ordering::Relaxed);
+                            }
+                            results.push((index, chunk, result));
+                        }
+                        results
+                    })
+                })
+                .collect::<Vec<_>>();
 
-            log_headers(response.headers());
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect::<Vec<_>>()
+        });
 
-            let status_code = response.status();
-            match response.status() {
-                StatusCode::OK => Ok(RequestResult::Done(thing::from_xml(
-                    &response.body_mut().read_to_string()?,
-                )?)),
-                _ => Ok(RequestResult::NotDone(status_code)),
+        // Chunks are taken in order, so every chunk before the first error has a result.
+        results.sort_by_key(|(index, _, _)| *index);
+        let mut ordered = Vec::new();
+        for (_, chunk, result) in results {
+            let stop = stop_on_error && result.is_err();
+            ordered.push((chunk, result));
+            if stop {
+                break;
             }
+        }
+
+        ordered
+    }
+
+    fn get_games_from_api(&self, ids: &[u32], options: &FetchOptions) -> error::Result<Vec<Game>> {
+        self.fetch_cached(
+            &thing_url(&self.url, ids),
+            self.cache_ttls.thing,
+            options,
+            thing::from_xml,
+        )
+    }
+
+    /// Like [`Self::fetch`], but uses the cache, if any, for responses up to `ttl` old.
+    fn fetch_cached<T>(
+        &self,
+        url: &str,
+        ttl: Duration,
+        options: &FetchOptions,
+        parse: impl Fn(&str) -> error::Result<T>,
+    ) -> error::Result<T> {
+        let Some(cache) = &self.cache else {
+            return self.fetch_with(url, options, parse);
+        };
+
+        if let Some(xml) = cache.get(url, ttl) {
+            match parse(&xml) {
+                Ok(t) => {
+                    debug!("Using cached response for {url}");
+                    return Ok(t);
+                }
+                Err(error) => debug!("Ignoring cached response for {url}: {error}"),
+            }
+        }
+
+        self.fetch_with(url, options, |xml| {
+            let t = parse(xml)?;
+            cache.put(url, xml);
+            Ok(t)
         })
     }
+
+    /// Calls the XML API, with retries, and parses the response with `parse`.
+    fn fetch<T>(&self, url: &str, parse: impl Fn(&str) -> error::Result<T>) -> error::Result<T> {
+        self.fetch_with(url, &FetchOptions::default(), parse)
+    }
+
+    /// Like [`Self::fetch`], with [`FetchOptions`].
+    fn fetch_with<T>(
+        &self,
+        url: &str,
+        options: &FetchOptions,
+        parse: impl Fn(&str) -> error::Result<T>,
+    ) -> error::Result<T> {
+        let request = HttpRequest {
+            url: url.to_owned(),
+            headers: self
+                .api_token
+                .iter()
+                .map(|api_token| ("Authorization".to_owned(), format!("Bearer {api_token}")))
+                .chain(self.headers.iter().cloned())
+                .collect(),
+            timeout: None,
+        };
+
+        request::do_request(
+            &self.retry_policy,
+            self.rate_limiter.as_ref(),
+            options,
+            url,
+            |timeout| {
+                let response = self.transport.get(&HttpRequest {
+                    timeout,
+                    ..request.clone()
+                })?;
+                log_headers(&response.headers);
+                request::handle_response(&response, url, &parse)
+            },
+        )
+    }
+}
+
+fn collection_url(base: &str, user: &str, include_expansions: bool) -> String {
+    let exclude_param = if include_expansions {
+        ""
+    } else {
+        "&excludesubtype=boardgameexpansion"
+    };
+
+    format!(
+        "{base}/xmlapi2/collection?username={user}&own=1&brief=1&subtype=boardgame{exclude_param}",
+        user = encode(user)
+    )
+}
+
+fn thing_url(base: &str, ids: &[u32]) -> String {
+    format!("{base}/xmlapi2/thing?id={ids}&stats=1", ids = join_ids(ids))
+}
+
+fn join_ids(ids: &[u32]) -> String {
+    ids.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Percent-encodes a query parameter value.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                char::from(byte).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Locks `mutex`, even if a thread panicked while holding it.
+///
+/// The data behind the crate's mutexes stays valid if an update is interrupted, e.g. a rate
+/// limiter's bucket or a cache, so one panic shouldn't make every later call fail or skip it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 #[cfg(feature = "moar-debug")]
-fn log_headers(headers: &HeaderMap<HeaderValue>) {
+fn log_headers(headers: &[(String, String)]) {
     headers.iter().for_each(|(name, value)| {
-        debug!(
-            "HEADER FOR RESEARCH {}: {}",
-            name,
-            value.to_str().unwrap_or("not a string")
-        );
+        debug!("HEADER FOR RESEARCH {}: {}", name, value);
     });
 }
 
 #[cfg(not(feature = "moar-debug"))]
-fn log_headers(_: &HeaderMap<HeaderValue>) {}
+fn log_headers(_: &[(String, String)]) {}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::{
+        Cancelled, DeadlineExceeded, Forbidden, InvalidUserError, NotFound, Unauthorized,
+        UnexpectedStatus,
+    };
+    use crate::{
+        BggClient, CacheTtls, CancellationToken, FetchOptions, HotType, HttpRequest, HttpResponse,
+        MemoryCache, MemoryGameCache, ProgressObserver, RetryBehavior, RetryPolicy, Transport,
+        encode, error, join_ids,
+    };
+    use std::collections::{HashMap, VecDeque};
+    use std::fs;
+    use std::sync::{Arc, Mutex, mpsc};
+    use std::thread;
+    use std::time::Duration;
+
+    /// Responds with `responses_by_url` for matching URLs, otherwise `responses` in order, and
+    /// records the requests.
+    #[derive(Clone, Default)]
+    struct FakeTransport {
+        responses: Arc<Mutex<VecDeque<HttpResponse>>>,
+        responses_by_url: Arc<Mutex<HashMap<String, HttpResponse>>>,
+        requests: Arc<Mutex<Vec<HttpRequest>>>,
+    }
+
+    impl FakeTransport {
+        fn respond(self, status: u16, file: &str) -> Self {
+            self.responses.lock().unwrap().push_back(HttpResponse {
+                status,
+                headers: vec![],
+                body: fs::read_to_string(file).expect("Reading file"),
+            });
+            self
+        }
+
+        fn respond_to(self, url: &str, status: u16, file: &str) -> Self {
+            self.responses_by_url.lock().unwrap().insert(
+                url.to_owned(),
+                HttpResponse {
+                    status,
+                    headers: vec![],
+                    body: fs::read_to_string(file).expect("Reading file"),
+                },
+            );
+            self
+        }
+
+        fn urls(&self) -> Vec<String> {
+            let requests = self.requests.lock().unwrap();
+            requests.iter().map(|request| request.url.clone()).collect()
+        }
+    }
+
+    impl Transport for FakeTransport {
+        fn get(&self, request: &HttpRequest) -> error::Result<HttpResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            if let Some(response) = self.responses_by_url.lock().unwrap().get(&request.url) {
+                return Ok(response.clone());
+            }
+            Ok(self
+                .responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("No more responses"))
+        }
+    }
+
+    #[test]
+    fn test_get_all_games_for_user() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/unixgeek.xml")
+            .respond(200, "test/enormity.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone());
+
+        let games = client
+            .get_all_games_for_user("unixgeek", false)
+            .expect("Getting games");
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(games[0].name, "Enormity");
+        assert_eq!(
+            transport.urls(),
+            vec![
+                "http://localhost/xmlapi2/collection?username=unixgeek&own=1&brief=1&subtype=boardgame&excludesubtype=boardgameexpansion",
+                "http://localhost/xmlapi2/thing?id=421,228660,39567&stats=1",
+            ]
+        );
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(
+            requests[0].headers,
+            vec![("Authorization".to_owned(), "Bearer API_TOKEN".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_get_library_for_users_invalid_user() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/invalid-username.xml")
+            .respond(200, "test/unixgeek.xml")
+            .respond(200, "test/enormity.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone());
+
+        let library = client
+            .get_library_for_users(&["nobody", "unixgeek"], false)
+            .expect("Getting library");
+
+        assert_eq!(library.invalid_users, vec!["nobody"]);
+        assert_eq!(library.games.len(), 1);
+        assert_eq!(transport.urls().len(), 3);
+    }
+
+    #[test]
+    fn test_get_collection_invalid_user() {
+        let transport = FakeTransport::default().respond(200, "test/invalid-username.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport);
+
+        let result = client.get_collection("nobody", false);
+
+        assert!(matches!(result, Err(InvalidUserError)));
+    }
+
+    #[test]
+    fn test_get_collection_retries() {
+        let transport = FakeTransport::default()
+            .respond(202, "test/empty-collection.xml")
+            .respond(503, "test/empty-collection.xml")
+            .respond(200, "test/unixgeek.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone())
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::ZERO,
+                server_errors: RetryBehavior::Fixed,
+                ..RetryPolicy::default()
+            });
+
+        let items = client
+            .get_collection("unixgeek", false)
+            .expect("Getting collection");
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(transport.urls().len(), 3);
+    }
+
+    #[test]
+    fn test_get_collection_server_error() {
+        let transport = FakeTransport::default().respond(500, "test/empty-collection.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone());
+
+        let result = client.get_collection("unixgeek", false);
+
+        assert!(matches!(result, Err(UnexpectedStatus { status: 500, .. })));
+        assert_eq!(transport.urls().len(), 1);
+    }
+
+    #[test]
+    fn test_get_all_games_for_user_cached() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/unixgeek.xml")
+            .respond(200, "test/enormity.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone())
+            .with_cache(MemoryCache::new(10));
+
+        let games = client
+            .get_all_games_for_user("unixgeek", false)
+            .expect("Getting games");
+        let cached_games = client
+            .get_all_games_for_user("unixgeek", false)
+            .expect("Getting cached games");
+
+        assert_eq!(games.len(), 1);
+        assert_eq!(cached_games.len(), 1);
+        assert_eq!(transport.urls().len(), 2);
+    }
+
+    #[test]
+    fn test_get_games_game_cache() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/enormity.xml")
+            .respond(200, "test/eclipse.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone())
+            .with_game_cache(MemoryGameCache::new());
+
+        let games = client.get_games(&[430350]).expect("Getting games");
+        assert_eq!(games[0].name, "Enormity");
+
+        let games = client
+            .get_games(&[246900, 430350])
+            .expect("Getting cached games");
+
+        assert_eq!(
+            games.iter().map(|game| game.id).collect::<Vec<u32>>(),
+            vec![246900, 430350]
+        );
+        assert_eq!(
+            transport.urls(),
+            vec![
+                "http://localhost/xmlapi2/thing?id=430350&stats=1",
+                "http://localhost/xmlapi2/thing?id=246900&stats=1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_games_concurrently() {
+        /// Holds the response to `held_url` until another request was answered, so the batches
+        /// finish out of order.
+        struct HoldingTransport {
+            transport: FakeTransport,
+            held_url: String,
+            answered: Mutex<mpsc::Sender<()>>,
+            released: Mutex<mpsc::Receiver<()>>,
+        }
+
+        impl Transport for HoldingTransport {
+            fn get(&self, request: &HttpRequest) -> error::Result<HttpResponse> {
+                let response = self.transport.get(request);
+                if request.url == self.held_url {
+                    self.released
+                        .lock()
+                        .unwrap()
+                        .recv_timeout(Duration::from_secs(5))
+                        .expect("Batches were not requested concurrently");
+                } else {
+                    self.answered.lock().unwrap().send(()).unwrap();
+                }
+                response
+            }
+        }
+
+        let mut ids = vec![430350];
+        ids.extend(1..20);
+        ids.push(246900);
+        let held_url = format!(
+            "http://localhost/xmlapi2/thing?id={}&stats=1",
+            join_ids(&ids[..20])
+        );
+        let transport = FakeTransport::default()
+            .respond_to(&held_url, 200, "test/enormity.xml")
+            .respond_to(
+                "http://localhost/xmlapi2/thing?id=246900&stats=1",
+                200,
+                "test/eclipse.xml",
+            );
+        let (answered, released) = mpsc::channel();
+        let client = BggClient::with_transport(
+            "http://localhost",
+            "API_TOKEN",
+            HoldingTransport {
+                transport: transport.clone(),
+                held_url,
+                answered: Mutex::new(answered),
+                released: Mutex::new(released),
+            },
+        )
+        .with_concurrency(4);
+
+        let games = client.get_games(&ids).expect("Getting games");
+
+        assert_eq!(
+            games.iter().map(|game| game.id).collect::<Vec<u32>>(),
+            vec![430350, 246900]
+        );
+        assert_eq!(transport.urls().len(), 2);
+    }
+
+    #[test]
+    fn test_get_games_concurrently_error() {
+        let ids = (1..=60).collect::<Vec<u32>>();
+        let transport = FakeTransport::default()
+            .respond_to(
+                &format!(
+                    "http://localhost/xmlapi2/thing?id={}&stats=1",
+                    join_ids(&ids[20..40])
+                ),
+                404,
+                "test/empty-collection.xml",
+            )
+            .respond(200, "test/enormity.xml")
+            .respond(200, "test/enormity.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport)
+            .with_concurrency(3);
+
+        let result = client.get_games(&ids);
+
+        assert!(matches!(result, Err(NotFound)));
+    }
+
+    /// Records progress events as strings.
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl ProgressObserver for RecordingObserver {
+        fn batch_started(&self, batch: usize, batches: usize, ids: &[u32]) {
+            let event = format!("batch_started {batch} {batches} {ids:?}");
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn batch_finished(&self, batch: usize, batches: usize) {
+            let event = format!("batch_finished {batch} {batches}");
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn queued(&self) {
+            self.events.lock().unwrap().push("queued".to_owned());
+        }
+
+        fn retry_waiting(&self, status: Option<u16>, delay: Duration) {
+            let event = format!("retry_waiting {status:?} {delay:?}");
+            self.events.lock().unwrap().push(event);
+        }
+
+        fn done(&self) {
+            self.events.lock().unwrap().push("done".to_owned());
+        }
+    }
+
+    #[test]
+    fn test_get_all_games_for_user_with_progress() {
+        let transport = FakeTransport::default()
+            .respond(202, "test/empty-collection.xml")
+            .respond(200, "test/unixgeek.xml")
+            .respond(200, "test/enormity.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport)
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::ZERO,
+                ..RetryPolicy::default()
+            });
+        let observer = RecordingObserver::default();
+
+        client
+            .get_all_games_for_user_with(
+                "unixgeek",
+                false,
+                &FetchOptions::default().with_progress(&observer),
+            )
+            .expect("Getting games");
+
+        assert_eq!(
+            *observer.events.lock().unwrap(),
+            vec![
+                "queued",
+                "retry_waiting Some(202) 0ns",
+                "batch_started 1 1 [421, 228660, 39567]",
+                "batch_finished 1 1",
+                "done",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_collection_cancelled() {
+        let transport = FakeTransport::default().respond(202, "test/empty-collection.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone())
+            .with_retry_policy(RetryPolicy {
+                base_delay: Duration::from_secs(60),
+                ..RetryPolicy::default()
+            });
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            cancel.cancel();
+        });
+
+        let result = client.get_collection_with(
+            "unixgeek",
+            false,
+            &FetchOptions::default().with_cancellation(&token),
+        );
+
+        assert!(matches!(result, Err(Cancelled)));
+        assert_eq!(transport.urls().len(), 1);
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_get_collection_deadline_exceeded() {
+        let transport = FakeTransport::default().respond(202, "test/empty-collection.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone());
+
+        let result = client.get_collection_with(
+            "unixgeek",
+            false,
+            &FetchOptions::default().with_timeout(Duration::from_secs(1)),
+        );
+
+        assert!(matches!(result, Err(DeadlineExceeded)));
+        let requests = transport.requests.lock().unwrap();
+        assert!(requests[0].timeout.is_some());
+    }
+
+    #[test]
+    fn test_get_games_partial() {
+        let mut ids = vec![246900, 430350];
+        ids.extend(3..=40);
+        let transport = FakeTransport::default()
+            .respond(200, "test/things-bad-poll.xml")
+            .respond(404, "test/empty-collection.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport);
+
+        let partial = client.get_games_partial(&ids).expect("Getting games");
+
+        assert_eq!(partial.games.len(), 1);
+        assert_eq!(partial.games[0].name, "Enormity");
+        assert_eq!(partial.failed.len(), 2);
+        assert_eq!(partial.failed[0].ids, vec![246900]);
+        assert_eq!(partial.failed[1].ids, ids[20..]);
+        assert!(matches!(partial.failed[1].error, NotFound));
+    }
+
+    #[test]
+    fn test_get_games_iter() {
+        let mut ids = vec![430350];
+        ids.extend(1..20);
+        ids.push(246900);
+        let transport = FakeTransport::default()
+            .respond(200, "test/enormity.xml")
+            .respond(200, "test/eclipse.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone());
+
+        let mut games = client.get_games_iter(&ids);
+
+        assert_eq!(games.next().unwrap().unwrap().id, 430350);
+        assert_eq!(transport.urls().len(), 1);
+        assert_eq!(games.next().unwrap().unwrap().id, 246900);
+        assert_eq!(transport.urls().len(), 2);
+        assert!(games.next().is_none());
+    }
+
+    #[test]
+    fn test_get_all_games_for_user_iter_error() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/unixgeek.xml")
+            .respond(404, "test/empty-collection.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport);
+
+        let mut games = client
+            .get_all_games_for_user_iter("unixgeek", false)
+            .expect("Getting collection");
+
+        assert!(games.next().unwrap().is_err());
+        assert!(games.next().is_none());
+    }
+
+    #[test]
+    fn test_get_guild_members() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/guild-page-1.xml")
+            .respond(200, "test/guild-page-2.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone());
+
+        let guild = client.get_guild(1299, true).expect("Getting guild");
+
+        assert_eq!(guild.members.map(|members| members.len()), Some(27));
+        assert_eq!(
+            transport.urls(),
+            vec![
+                "http://localhost/xmlapi2/guild?id=1299&members=1&page=1",
+                "http://localhost/xmlapi2/guild?id=1299&members=1&page=2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_token() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/eclipse.xml")
+            .respond(401, "test/empty-collection.xml")
+            .respond(403, "test/empty-collection.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone());
+
+        assert!(client.validate_token().is_ok());
+        assert!(matches!(client.validate_token(), Err(Unauthorized)));
+        assert!(matches!(client.validate_token(), Err(Forbidden)));
+        assert_eq!(
+            transport.urls()[0],
+            "http://localhost/xmlapi2/thing?id=13&stats=1"
+        );
+    }
+
+    #[test]
+    fn test_without_token() {
+        let transport = FakeTransport::default().respond(200, "test/hot-boardgame.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone())
+            .without_api_token();
+
+        client
+            .get_hot(HotType::BoardGame)
+            .expect("Getting hot list");
+
+        let requests = transport.requests.lock().unwrap();
+        assert!(requests[0].headers.is_empty());
+        assert!(BggClient::without_token().api_token.is_none());
+    }
+
+    #[test]
+    fn test_headers() {
+        let transport = FakeTransport::default().respond(200, "test/hot-boardgame.xml");
+        let client = BggClient {
+            headers: vec![("X-Team".to_owned(), "games".to_owned())],
+            ..BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone())
+        };
+
+        client
+            .get_hot(HotType::BoardGame)
+            .expect("Getting hot list");
+
+        let requests = transport.requests.lock().unwrap();
+        assert_eq!(
+            requests[0].headers,
+            vec![
+                ("Authorization".to_owned(), "Bearer API_TOKEN".to_owned()),
+                ("X-Team".to_owned(), "games".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_collection_expired() {
+        let transport = FakeTransport::default()
+            .respond(200, "test/unixgeek.xml")
+            .respond(200, "test/unixgeek.xml");
+        let client = BggClient::with_transport("http://localhost", "API_TOKEN", transport.clone())
+            .with_cache(MemoryCache::new(10))
+            .with_cache_ttls(CacheTtls {
+                collection: Duration::ZERO,
+                ..CacheTtls::default()
+            });
+
+        client.get_collection("unixgeek", false).unwrap();
+        client.get_collection("unixgeek", false).unwrap();
+
+        assert_eq!(transport.urls().len(), 2);
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("unixgeek"), "unixgeek");
+        assert_eq!(encode("dicetower fan"), "dicetower%20fan");
+        assert_eq!(encode("Catan & Co"), "Catan%20%26%20Co");
+        assert_eq!(encode("Ühr"), "%C3%9Chr");
+    }
+}
@@ -0,0 +1,92 @@
+//! A library of games combined from the collections of several users, e.g. the members of a guild.
+use crate::{CollectionItem, Game};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// The combined games of several users.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct Library {
+    /// The games, keyed by id.
+    pub games: BTreeMap<u32, LibraryGame>,
+    /// Users that could not be included because they do not exist.
+    pub invalid_users: Vec<String>,
+}
+
+/// A game in a [`Library`] and the users that own it.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct LibraryGame {
+    pub game: Game,
+    pub owners: Vec<String>,
+}
+
+/// Maps each game id in `collections` to the users that own it.
+pub(super) fn owners_by_id(
+    collections: Vec<(String, Vec<CollectionItem>)>,
+) -> BTreeMap<u32, Vec<String>> {
+    let mut owners_by_id: BTreeMap<u32, Vec<String>> = BTreeMap::new();
+
+    for (user, items) in collections {
+        for item in items {
+            let owners = owners_by_id.entry(item.id).or_default();
+            // A collection can have more than one copy of a game.
+            if !owners.contains(&user) {
+                owners.push(user.clone());
+            }
+        }
+    }
+
+    owners_by_id
+}
+
+/// Combines `games` with their owners from [`owners_by_id`].
+pub(super) fn build(
+    games: Vec<Game>,
+    mut owners_by_id: BTreeMap<u32, Vec<String>>,
+    invalid_users: Vec<String>,
+) -> Library {
+    let games = games
+        .into_iter()
+        .map(|game| {
+            let owners = owners_by_id.remove(&game.id).unwrap_or_default();
+            (game.id, LibraryGame { game, owners })
+        })
+        .collect();
+
+    Library {
+        games,
+        invalid_users,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::CollectionItem;
+    use crate::library::owners_by_id;
+
+    fn item(id: u32, name: &str) -> CollectionItem {
+        CollectionItem {
+            id,
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_owners_by_id() {
+        let owners_by_id = owners_by_id(vec![
+            (
+                "unixgeek".to_owned(),
+                vec![
+                    item(421, "1830"),
+                    item(246900, "Eclipse"),
+                    item(421, "1830"),
+                ],
+            ),
+            ("meeple".to_owned(), vec![item(246900, "Eclipse")]),
+            ("nobody".to_owned(), vec![]),
+        ]);
+
+        assert_eq!(owners_by_id.len(), 2);
+        assert_eq!(owners_by_id[&421], vec!["unixgeek"]);
+        assert_eq!(owners_by_id[&246900], vec!["unixgeek", "meeple"]);
+    }
+}
@@ -0,0 +1,134 @@
+//! Options for a single call to [`crate::BggClient`].
+use crate::error::Error::{Cancelled, DeadlineExceeded};
+use crate::{CancellationToken, ProgressObserver, error};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Options for a single call, for the `*_with` methods of [`crate::BggClient`].
+///
+/// ```no_run
+/// # use bgg_lib::{error, BggClient, CancellationToken, FetchOptions};
+/// # use std::time::Duration;
+/// # fn main() -> error::Result<()>{
+/// let client = BggClient::new("API_TOKEN");
+/// let token = CancellationToken::new();
+/// let options = FetchOptions::default()
+///     .with_cancellation(&token)
+///     .with_timeout(Duration::from_secs(30));
+/// let games = client.get_all_games_for_user_with("unixgeek", false, &options)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Copy, Default)]
+pub struct FetchOptions<'a> {
+    /// Receives progress events.
+    pub progress: Option<&'a dyn ProgressObserver>,
+    /// Cancels the call.
+    pub cancellation: Option<&'a CancellationToken>,
+    /// When to give up on the call.
+    pub deadline: Option<Instant>,
+}
+
+impl<'a> FetchOptions<'a> {
+    /// Reports progress to `progress`.
+    pub fn with_progress(mut self, progress: &'a dyn ProgressObserver) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Returns [`error::Error::Cancelled`] when `cancellation` is cancelled.
+    pub fn with_cancellation(mut self, cancellation: &'a CancellationToken) -> Self {
+        self.cancellation = Some(cancellation);
+        self
+    }
+
+    /// Returns [`error::Error::DeadlineExceeded`] if the call has not finished by `deadline`.
+    ///
+    /// The call fails as soon as waiting to retry would pass the deadline, and each request is
+    /// sent with a timeout of the time that is left.
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Like [`Self::with_deadline`], with a deadline of `timeout` from now.
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Calls `event` with the [`ProgressObserver`], if any.
+    pub(super) fn notify(&self, event: impl FnOnce(&dyn ProgressObserver)) {
+        if let Some(progress) = self.progress {
+            event(progress);
+        }
+    }
+
+    /// Returns the time left before the deadline, if any, or an error if the call should stop.
+    pub(super) fn check(&self) -> error::Result<Option<Duration>> {
+        if self
+            .cancellation
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(Cancelled);
+        }
+
+        match self.deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(left) if !left.is_zero() => Ok(Some(left)),
+                _ => Err(DeadlineExceeded),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Sleeps for `duration`, unless cancelled, or the deadline would pass first.
+    pub(super) fn sleep(&self, duration: Duration) -> error::Result<()> {
+        if self.check()?.is_some_and(|left| left < duration) {
+            return Err(DeadlineExceeded);
+        }
+
+        match self.cancellation {
+            Some(cancellation) if cancellation.sleep(duration) => Err(Cancelled),
+            Some(_) => Ok(()),
+            None => {
+                thread::sleep(duration);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::{Cancelled, DeadlineExceeded};
+    use crate::{CancellationToken, FetchOptions};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_check() {
+        assert!(matches!(FetchOptions::default().check(), Ok(None)));
+
+        let options = FetchOptions::default().with_timeout(Duration::from_secs(60));
+        assert!(matches!(options.check(), Ok(Some(_))));
+
+        let options = FetchOptions::default().with_deadline(Instant::now());
+        assert!(matches!(options.check(), Err(DeadlineExceeded)));
+
+        let token = CancellationToken::new();
+        let options = FetchOptions::default().with_cancellation(&token);
+        assert!(matches!(options.check(), Ok(None)));
+        token.cancel();
+        assert!(matches!(options.check(), Err(Cancelled)));
+    }
+
+    #[test]
+    fn test_sleep_past_deadline() {
+        let options = FetchOptions::default().with_timeout(Duration::from_secs(60));
+
+        assert!(matches!(
+            options.sleep(Duration::from_secs(120)),
+            Err(DeadlineExceeded)
+        ));
+        assert!(options.sleep(Duration::from_millis(1)).is_ok());
+    }
+}
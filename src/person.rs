@@ -0,0 +1,166 @@
+//! The responses from the `/boardgamedesigner`, `/boardgameartist` and `/boardgamepublisher`
+//! endpoints of the legacy XML API (v1).
+//!
+//! XML API2 has no equivalent endpoints. The responses do not include the id, so it is passed in.
+use crate::error::Error::NotFound;
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn person_from_xml(xml: &str, id: u32, role: PersonRole) -> error::Result<Person> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Person XML: {}", xml);
+
+    xml::check_errors(role.as_str(), xml)?;
+    let entry = xml::from_str::<PeopleXml>(role.as_str(), xml)?
+        .inner
+        .into_iter()
+        .next()
+        .ok_or(NotFound)?;
+
+    Ok(Person {
+        id,
+        name: entry.name,
+        description: entry.description,
+        thumbnail: entry.thumbnail,
+        image: entry.image,
+    })
+}
+
+pub(super) fn company_from_xml(xml: &str, id: u32) -> error::Result<Company> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Company XML: {}", xml);
+
+    xml::check_errors("boardgamepublisher", xml)?;
+    let entry = xml::from_str::<CompaniesXml>("boardgamepublisher", xml)?
+        .inner
+        .into_iter()
+        .next()
+        .ok_or(NotFound)?;
+
+    Ok(Company {
+        id,
+        name: entry.name,
+        description: entry.description,
+        thumbnail: entry.thumbnail,
+        image: entry.image,
+    })
+}
+
+/// The role a person is looked up by. Designers and artists share the same ids.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PersonRole {
+    #[default]
+    Designer,
+    Artist,
+}
+
+impl PersonRole {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            PersonRole::Designer => "boardgamedesigner",
+            PersonRole::Artist => "boardgameartist",
+        }
+    }
+}
+
+/// Represents a person, e.g. a designer or an artist.
+///
+/// The description is HTML.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Person {
+    pub id: u32,
+    pub name: String,
+    pub description: Option<String>,
+    pub thumbnail: Option<String>,
+    pub image: Option<String>,
+}
+
+/// Represents a company, e.g. a publisher.
+///
+/// The description is HTML.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Company {
+    pub id: u32,
+    pub name: String,
+    pub description: Option<String>,
+    pub thumbnail: Option<String>,
+    pub image: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PeopleXml {
+    #[serde(rename = "person", default)]
+    inner: Vec<EntryXml>,
+}
+
+#[derive(Deserialize)]
+struct CompaniesXml {
+    #[serde(rename = "company", default)]
+    inner: Vec<EntryXml>,
+}
+
+// Both <person> and <company> look like this.
+#[derive(Deserialize)]
+struct EntryXml {
+    name: String,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    description: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    thumbnail: Option<String>,
+    #[serde(default, deserialize_with = "xml::empty_as_none")]
+    image: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::NotFound;
+    use crate::person::{PersonRole, company_from_xml, person_from_xml};
+    use std::fs;
+
+    #[test]
+    fn test_person_from_xml() {
+        let person = person_from_xml(
+            &fs::read_to_string("test/person-designer.xml").expect("Reading file"),
+            11,
+            PersonRole::Designer,
+        )
+        .expect("Parsing XML");
+
+        assert_eq!(person.id, 11);
+        assert_eq!(person.name, "Klaus Teuber");
+        assert_eq!(
+            person.description.as_deref(),
+            Some("German game designer, best known for CATAN.<br/><br/>Born 1952.")
+        );
+        assert_eq!(
+            person.image.as_deref(),
+            Some("https://cf.geekdo-images.com/example__original/img/teuber.jpg")
+        );
+    }
+
+    #[test]
+    fn test_company_from_xml() {
+        let company = company_from_xml(
+            &fs::read_to_string("test/company-publisher.xml").expect("Reading file"),
+            37,
+        )
+        .expect("Parsing XML");
+
+        assert_eq!(company.id, 37);
+        assert_eq!(company.name, "KOSMOS");
+        assert_eq!(company.description, None);
+        assert_eq!(company.thumbnail, None);
+        assert_eq!(company.image, None);
+    }
+
+    #[test]
+    fn test_person_from_xml_not_found() {
+        let result = person_from_xml(
+            r#"<people termsofuse="https://boardgamegeek.com/xmlapi/termsofuse"></people>"#,
+            1,
+            PersonRole::Designer,
+        );
+
+        assert!(matches!(result, Err(NotFound)));
+    }
+}
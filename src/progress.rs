@@ -0,0 +1,32 @@
+//! Progress reporting for calls that can take a long time.
+use std::time::Duration;
+
+/// Receives progress events from a [`crate::BggClient`] call, e.g. to show a progress bar.
+///
+/// All of the methods do nothing by default, so implement only the ones you need. Batches can be
+/// requested concurrently (see [`crate::BggClient::with_concurrency`]), so events can come from
+/// several threads, and batches can finish in any order.
+pub trait ProgressObserver: Send + Sync {
+    /// A batch of games is being requested. `batch` counts from 1 to `batches`.
+    fn batch_started(&self, batch: usize, batches: usize, ids: &[u32]) {
+        let _ = (batch, batches, ids);
+    }
+
+    /// A batch of games was received.
+    fn batch_finished(&self, batch: usize, batches: usize) {
+        let _ = (batch, batches);
+    }
+
+    /// The XML API queued the request, responding with `202 Accepted`. It is retried after
+    /// [`Self::retry_waiting`].
+    fn queued(&self) {}
+
+    /// A request is retried after `delay`. `status` is the status code of the response, or `None`
+    /// if there was no response.
+    fn retry_waiting(&self, status: Option<u16>, delay: Duration) {
+        let _ = (status, delay);
+    }
+
+    /// The call finished successfully.
+    fn done(&self) {}
+}
@@ -0,0 +1,130 @@
+//! Client-side rate limiting, to avoid `429 Too Many Requests` rather than retry after it.
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A token bucket rate limiter.
+///
+/// Every request takes a token, and tokens are added at `requests_per_second`, up to `burst`.
+/// When there are no tokens left, the request waits for the next one.
+///
+/// Cloning is cheap, and clones share the same bucket. Give clones to several clients, possibly
+/// on different threads, to limit the requests they make together, e.g. when they use the same
+/// API token.
+///
+/// ```
+/// # use bgg_lib::{BggClient, RateLimiter};
+/// let rate_limiter = RateLimiter::new(2.0, 5);
+/// let client = BggClient::new("API_TOKEN").with_rate_limiter(rate_limiter.clone());
+/// let other_client = BggClient::new("API_TOKEN").with_rate_limiter(rate_limiter);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    requests_per_second: f64,
+    burst: f64,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    // Negative when requests are waiting for tokens.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Creates a [`RateLimiter`] that starts with `burst` tokens.
+    ///
+    /// # Panics
+    /// If `requests_per_second` is not positive, or `burst` is 0.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        assert!(
+            requests_per_second > 0.0,
+            "requests_per_second must be positive"
+        );
+        assert!(burst > 0, "burst must be at least 1");
+
+        Self {
+            requests_per_second,
+            burst: f64::from(burst),
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(burst),
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Takes a token, and returns how long to wait before sending the request.
+    pub(super) fn reserve(&self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    fn reserve_at(&self, now: Instant) -> Duration {
+        let mut bucket = crate::lock(&self.bucket);
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(self.burst);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.requests_per_second)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rate_limit::RateLimiter;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_reserve() {
+        let rate_limiter = RateLimiter::new(2.0, 2);
+        let now = Instant::now();
+
+        let waits = (0..4)
+            .map(|_| rate_limiter.reserve_at(now))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            waits,
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_secs(1)
+            ]
+        );
+
+        // The waiting requests used the tokens added in the meantime.
+        assert_eq!(
+            rate_limiter.reserve_at(now + Duration::from_secs(1)),
+            Duration::from_millis(500)
+        );
+    }
+
+    #[test]
+    fn test_reserve_refills_up_to_burst() {
+        let rate_limiter = RateLimiter::new(1.0, 2);
+        let now = Instant::now();
+
+        rate_limiter.reserve_at(now);
+        rate_limiter.reserve_at(now);
+
+        let later = now + Duration::from_secs(60);
+        assert_eq!(rate_limiter.reserve_at(later), Duration::ZERO);
+        assert_eq!(rate_limiter.reserve_at(later), Duration::ZERO);
+        assert_eq!(rate_limiter.reserve_at(later), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_clones_share_bucket() {
+        let rate_limiter = RateLimiter::new(1.0, 1);
+        let clone = rate_limiter.clone();
+        let now = Instant::now();
+
+        assert_eq!(rate_limiter.reserve_at(now), Duration::ZERO);
+        assert_eq!(clone.reserve_at(now), Duration::from_secs(1));
+    }
+}
@@ -1,51 +1,362 @@
 //! A DRY way of calling the XML API with retry logic.
-use crate::error;
-use crate::error::Error::XmlApiError;
+use crate::error::Error::{
+    Forbidden, HttpError, NotFound, RetriesExhausted, Unauthorized, UnexpectedStatus,
+};
+use crate::options::FetchOptions;
+use crate::rate_limit::RateLimiter;
+use crate::retry::{RetryBehavior, RetryPolicy, parse_retry_after};
+use crate::{HttpResponse, error};
 use log::debug;
-use std::thread;
 use std::time::Duration;
 use ureq::http::StatusCode;
 
-const MAX_RETRIES: u8 = 5;
-// Based on observation, 1 second is not enough. Ends up being wait 1, then wait 2, so a total of 3 seconds.
-const WAIT_SECONDS: u8 = 2;
-const WAIT_MULTIPLIER: u8 = 2;
+// Enough of a body to explain an unexpected status, without keeping a whole page of HTML.
+const BODY_SNIPPET_LEN: usize = 200;
 
 pub(super) enum RequestResult<T> {
     Done(T),
-    NotDone(StatusCode),
+    NotDone(NotDone),
 }
 
-pub(super) fn do_request<F, T>(exec_request: F) -> error::Result<T>
+/// A response that was not `200 OK`.
+pub(super) struct NotDone {
+    pub(super) status_code: StatusCode,
+    pub(super) retry_after: Option<Duration>,
+    pub(super) body_snippet: String,
+}
+
+/// Parses a `200 OK` response with `parse`, otherwise returns the status code to be retried.
+pub(super) fn handle_response<T>(
+    response: &HttpResponse,
+    url: &str,
+    parse: impl Fn(&str) -> error::Result<T>,
+) -> error::Result<RequestResult<T>> {
+    let body_snippet = || snippet(&response.body);
+    let status_code = StatusCode::from_u16(response.status).map_err(|_| UnexpectedStatus {
+        status: response.status,
+        url: url.to_owned(),
+        body_snippet: body_snippet(),
+    })?;
+
+    match status_code {
+        StatusCode::OK => Ok(RequestResult::Done(parse(&response.body)?)),
+        _ => Ok(RequestResult::NotDone(NotDone {
+            status_code,
+            retry_after: response.header("Retry-After").and_then(parse_retry_after),
+            body_snippet: body_snippet(),
+        })),
+    }
+}
+
+/// Returns the start of `body`, trimmed.
+fn snippet(body: &str) -> String {
+    body.trim().chars().take(BODY_SNIPPET_LEN).collect()
+}
+
+/// Returns the error for a response that is not retried.
+fn status_error(not_done: &NotDone, url: &str) -> error::Error {
+    match not_done.status_code {
+        StatusCode::UNAUTHORIZED => Unauthorized,
+        StatusCode::FORBIDDEN => Forbidden,
+        StatusCode::NOT_FOUND => NotFound,
+        status_code => UnexpectedStatus {
+            status: status_code.as_u16(),
+            url: url.to_owned(),
+            body_snippet: not_done.body_snippet.clone(),
+        },
+    }
+}
+
+/// Tracks the retries of a single request, so the blocking and async clients behave the same.
+pub(super) struct Retry<'a> {
+    policy: &'a RetryPolicy,
+    retries: u32,
+}
+
+impl<'a> Retry<'a> {
+    pub(super) fn new(policy: &'a RetryPolicy) -> Self {
+        Self { policy, retries: 0 }
+    }
+
+    /// Returns how long to wait before trying again after a response from `url` that was not
+    /// `200 OK`, or an error if we should give up.
+    pub(super) fn after_status(
+        &mut self,
+        not_done: &NotDone,
+        url: &str,
+    ) -> error::Result<Duration> {
+        let status_code = not_done.status_code;
+        let behavior = match status_code {
+            StatusCode::ACCEPTED => self.policy.accepted,
+            StatusCode::TOO_MANY_REQUESTS => self.policy.too_many_requests,
+            status_code if status_code.is_server_error() => self.policy.server_errors,
+            _ => RetryBehavior::Fail,
+        };
+
+        let Some(delay) = self.next_delay(behavior, not_done.retry_after) else {
+            return Err(status_error(not_done, url));
+        };
+        if self.is_exhausted() {
+            return Err(RetriesExhausted {
+                attempts: self.retries,
+                last_status: Some(status_code.as_u16()),
+                last_error: None,
+            });
+        }
+
+        debug!("Status code {status_code}, sleeping {delay:?}");
+        Ok(delay)
+    }
+
+    /// Returns how long to wait before trying again after `error`, or an error if we should
+    /// give up.
+    pub(super) fn after_error(&mut self, error: error::Error) -> error::Result<Duration> {
+        if !matches!(error, HttpError(_)) {
+            return Err(error);
+        }
+
+        let Some(delay) = self.next_delay(self.policy.transport_errors, None) else {
+            return Err(error);
+        };
+        if self.is_exhausted() {
+            return Err(RetriesExhausted {
+                attempts: self.retries,
+                last_status: None,
+                last_error: Some(Box::new(error)),
+            });
+        }
+
+        debug!("{error}, sleeping {delay:?}");
+        Ok(delay)
+    }
+
+    fn next_delay(
+        &mut self,
+        behavior: RetryBehavior,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        let delay = self.policy.delay(behavior, self.retries, retry_after);
+        if delay.is_some() {
+            self.retries += 1;
+        }
+
+        delay
+    }
+
+    fn is_exhausted(&self) -> bool {
+        self.retries >= self.policy.max_attempts
+    }
+}
+
+/// Returns how long to wait before sending a request, according to `rate_limiter`.
+pub(super) fn rate_limit(rate_limiter: Option<&RateLimiter>) -> Duration {
+    let wait = rate_limiter.map_or(Duration::ZERO, RateLimiter::reserve);
+    if !wait.is_zero() {
+        debug!("Rate limited, sleeping {wait:?}");
+    }
+
+    wait
+}
+
+/// Calls `exec_request` until it is done, retrying according to `policy`.
+///
+/// `exec_request` is passed the time left before the deadline in `options`, if any, to use as a
+/// timeout.
+pub(super) fn do_request<F, T>(
+    policy: &RetryPolicy,
+    rate_limiter: Option<&RateLimiter>,
+    options: &FetchOptions,
+    url: &str,
+    exec_request: F,
+) -> error::Result<T>
 where
-    F: Fn() -> error::Result<RequestResult<T>>,
+    F: Fn(Option<Duration>) -> error::Result<RequestResult<T>>,
 {
-    let mut retries = 0;
-    let mut wait_seconds = WAIT_SECONDS;
+    let mut retry = Retry::new(policy);
 
     loop {
-        if retries > MAX_RETRIES {
-            return Err(XmlApiError("Too many retries".to_owned()));
-        }
-
-        match exec_request()? {
-            RequestResult::Done(t) => return Ok(t),
-            RequestResult::NotDone(status_code) => match status_code {
-                StatusCode::TOO_MANY_REQUESTS => {
-                    debug!("Too many requests, sleeping {wait_seconds}");
-                    thread::sleep(Duration::from_secs(wait_seconds.into()));
-                    retries += 1;
-                    wait_seconds *= WAIT_MULTIPLIER;
-                }
-                StatusCode::ACCEPTED => {
-                    debug!("Accepted");
-                    thread::sleep(Duration::from_secs(wait_seconds.into()));
-                    retries += 1;
-                }
-                status_code => {
-                    return Err(XmlApiError(format!("Unexpected status code {status_code}")));
+        let wait = rate_limit(rate_limiter);
+        if !wait.is_zero() {
+            options.sleep(wait)?;
+        }
+
+        let delay = match exec_request(options.check()?) {
+            Ok(RequestResult::Done(t)) => return Ok(t),
+            Ok(RequestResult::NotDone(not_done)) => {
+                let delay = retry.after_status(&not_done, url)?;
+                let status_code = not_done.status_code;
+                if status_code == StatusCode::ACCEPTED {
+                    options.notify(|progress| progress.queued());
                 }
-            },
+                options
+                    .notify(|progress| progress.retry_waiting(Some(status_code.as_u16()), delay));
+                delay
+            }
+            Err(error) => {
+                // A request that timed out because of the deadline is not worth retrying.
+                options.check()?;
+                let delay = retry.after_error(error)?;
+                options.notify(|progress| progress.retry_waiting(None, delay));
+                delay
+            }
+        };
+        options.sleep(delay)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::{
+        Forbidden, HttpError, NotFound, RetriesExhausted, Unauthorized, UnexpectedStatus,
+        XmlApiError,
+    };
+    use crate::request::{NotDone, Retry};
+    use crate::retry::{RetryBehavior, RetryPolicy};
+    use std::time::Duration;
+    use ureq::http::StatusCode;
+
+    const URL: &str = "http://localhost/xmlapi2/thing?id=1";
+
+    fn not_done(status_code: StatusCode, retry_after: Option<Duration>) -> NotDone {
+        NotDone {
+            status_code,
+            retry_after,
+            body_snippet: "<html>Oops</html>".to_owned(),
         }
     }
+
+    #[test]
+    fn test_after_status_too_many_requests() {
+        let policy = RetryPolicy::default();
+        let mut retry = Retry::new(&policy);
+        let not_done = not_done(StatusCode::TOO_MANY_REQUESTS, None);
+
+        let waits = (0..5)
+            .map(|_| retry.after_status(&not_done, URL).unwrap())
+            .map(|wait| wait.as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(waits, vec![2, 4, 8, 16, 32]);
+
+        let result = retry.after_status(&not_done, URL);
+        assert!(matches!(
+            result,
+            Err(RetriesExhausted {
+                attempts: 6,
+                last_status: Some(429),
+                last_error: None
+            })
+        ));
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            "Too many retries (6 attempts)"
+        );
+    }
+
+    #[test]
+    fn test_after_status_accepted() {
+        let policy = RetryPolicy::default();
+        let mut retry = Retry::new(&policy);
+        let not_done = not_done(StatusCode::ACCEPTED, None);
+
+        assert_eq!(
+            retry.after_status(&not_done, URL).unwrap(),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            retry.after_status(&not_done, URL).unwrap(),
+            Duration::from_secs(2)
+        );
+    }
+
+    #[test]
+    fn test_after_status_retry_after() {
+        let policy = RetryPolicy::default();
+        let mut retry = Retry::new(&policy);
+        let not_done = not_done(StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(7)));
+
+        assert_eq!(
+            retry.after_status(&not_done, URL).unwrap(),
+            Duration::from_secs(7)
+        );
+    }
+
+    #[test]
+    fn test_after_status_unexpected() {
+        let policy = RetryPolicy::default();
+
+        let result = Retry::new(&policy).after_status(&not_done(StatusCode::NOT_FOUND, None), URL);
+        assert!(matches!(result, Err(NotFound)));
+
+        let result =
+            Retry::new(&policy).after_status(&not_done(StatusCode::UNAUTHORIZED, None), URL);
+        assert!(matches!(result, Err(Unauthorized)));
+
+        let result = Retry::new(&policy).after_status(&not_done(StatusCode::FORBIDDEN, None), URL);
+        assert!(matches!(result, Err(Forbidden)));
+
+        let result =
+            Retry::new(&policy).after_status(&not_done(StatusCode::SERVICE_UNAVAILABLE, None), URL);
+        match result {
+            Err(UnexpectedStatus {
+                status,
+                url,
+                body_snippet,
+            }) => {
+                assert_eq!(status, 503);
+                assert_eq!(url, URL);
+                assert_eq!(body_snippet, "<html>Oops</html>");
+            }
+            _ => panic!("Expected UnexpectedStatus"),
+        }
+
+        let policy = RetryPolicy {
+            server_errors: RetryBehavior::Fixed,
+            ..RetryPolicy::default()
+        };
+        let result =
+            Retry::new(&policy).after_status(&not_done(StatusCode::SERVICE_UNAVAILABLE, None), URL);
+        assert_eq!(result.unwrap(), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_after_error() {
+        let policy = RetryPolicy::default();
+
+        let result = Retry::new(&policy).after_error(HttpError("connection refused".into()));
+        assert!(matches!(result, Err(HttpError(_))));
+
+        let policy = RetryPolicy {
+            max_attempts: 2,
+            transport_errors: RetryBehavior::Backoff,
+            ..RetryPolicy::default()
+        };
+        let mut retry = Retry::new(&policy);
+        assert_eq!(
+            retry
+                .after_error(HttpError("connection refused".into()))
+                .unwrap(),
+            Duration::from_secs(2)
+        );
+
+        let result = retry.after_error(XmlApiError("Not retried".to_owned()));
+        assert!(matches!(result, Err(XmlApiError(_))));
+
+        let result = retry.after_error(HttpError("connection refused".into()));
+        match result {
+            Err(RetriesExhausted {
+                last_status: None,
+                last_error: Some(last_error),
+                ..
+            }) => assert!(matches!(*last_error, HttpError(_))),
+            _ => panic!("Expected RetriesExhausted"),
+        }
+    }
+
+    #[test]
+    fn test_no_retries() {
+        let policy = RetryPolicy::no_retries();
+
+        let result = Retry::new(&policy).after_status(&not_done(StatusCode::ACCEPTED, None), URL);
+        assert!(matches!(result, Err(RetriesExhausted { attempts: 1, .. })));
+    }
 }
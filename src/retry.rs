@@ -0,0 +1,210 @@
+//! How [`crate::BggClient`] retries requests.
+//!
+//! The XML API responds with `202 Accepted` when a request has been queued, e.g. a large
+//! collection, and with `429 Too Many Requests` when it is throttling. Both are retried.
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+/// What to do when a request fails in a particular way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RetryBehavior {
+    /// Don't retry; return an error.
+    Fail,
+    /// Retry after [`RetryPolicy::base_delay`] every time.
+    Fixed,
+    /// Retry after [`RetryPolicy::base_delay`], multiplied by [`RetryPolicy::multiplier`] for
+    /// each previous retry.
+    Backoff,
+}
+
+/// Configures how requests are retried.
+///
+/// The default waits 2 seconds between attempts, doubling the wait when throttled, and gives up
+/// after 6 attempts. Server errors and transport errors are not retried.
+///
+/// ```
+/// # use bgg_lib::{BggClient, RetryBehavior, RetryPolicy};
+/// # use std::time::Duration;
+/// // Be patient in a batch job.
+/// let client = BggClient::new("API_TOKEN").with_retry_policy(RetryPolicy {
+///     max_attempts: 10,
+///     jitter: 0.25,
+///     max_delay: Duration::from_secs(300),
+///     server_errors: RetryBehavior::Backoff,
+///     transport_errors: RetryBehavior::Backoff,
+///     ..RetryPolicy::default()
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the first. 1 means never retry.
+    pub max_attempts: u32,
+    /// The delay before the first retry.
+    pub base_delay: Duration,
+    /// Multiplies the delay for each retry, when using [`RetryBehavior::Backoff`].
+    pub multiplier: f64,
+    /// Randomly changes each delay by up to this fraction of it, from 0.0 to 1.0, so that many
+    /// clients don't retry at the same time.
+    pub jitter: f64,
+    /// The longest delay, including one from a `Retry-After` header.
+    pub max_delay: Duration,
+    /// What to do when a response is `202 Accepted`, i.e. the request was queued.
+    pub accepted: RetryBehavior,
+    /// What to do when a response is `429 Too Many Requests`.
+    pub too_many_requests: RetryBehavior,
+    /// What to do when a response is a `5xx` server error.
+    pub server_errors: RetryBehavior,
+    /// What to do when there is no response, e.g. the connection failed.
+    pub transport_errors: RetryBehavior,
+    /// Wait for the number of seconds in a `Retry-After` header instead of the computed delay.
+    pub honor_retry_after: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 6,
+            // Based on observation, 1 second is not enough. Ends up being wait 1, then wait 2, so a total of 3 seconds.
+            base_delay: Duration::from_secs(2),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_delay: Duration::from_secs(60),
+            accepted: RetryBehavior::Fixed,
+            too_many_requests: RetryBehavior::Backoff,
+            server_errors: RetryBehavior::Fail,
+            transport_errors: RetryBehavior::Fail,
+            honor_retry_after: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn no_retries() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the delay before the retry that follows `retries` previous retries, or `None` if
+    /// `behavior` is [`RetryBehavior::Fail`].
+    pub(super) fn delay(
+        &self,
+        behavior: RetryBehavior,
+        retries: u32,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        let delay = match (behavior, retry_after) {
+            (RetryBehavior::Fail, _) => return None,
+            (_, Some(retry_after)) if self.honor_retry_after => retry_after,
+            (RetryBehavior::Fixed, _) => self.with_jitter(self.base_delay),
+            (RetryBehavior::Backoff, _) => {
+                let exponent = i32::try_from(retries).unwrap_or(i32::MAX);
+                let delay = self.base_delay.as_secs_f64() * self.multiplier.powi(exponent);
+                self.with_jitter(Duration::try_from_secs_f64(delay).unwrap_or(self.max_delay))
+            }
+        };
+
+        Some(delay.min(self.max_delay))
+    }
+
+    fn with_jitter(&self, delay: Duration) -> Duration {
+        // A NaN or infinite jitter would make `mul_f64` panic, so it is treated as no jitter.
+        if !self.jitter.is_finite() || self.jitter <= 0.0 {
+            return delay;
+        }
+        let jitter = self.jitter.min(1.0);
+
+        // A factor between 1 - jitter and 1 + jitter.
+        let factor = 1.0 - jitter + 2.0 * jitter * random_fraction();
+        delay.mul_f64(factor)
+    }
+}
+
+/// Returns a number from 0.0 to 1.0. Good enough for jitter, without another dependency.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+    (random >> 11) as f64 / (1_u64 << 53) as f64
+}
+
+/// Parses the value of a `Retry-After` header. Only the number of seconds is supported, not a
+/// date.
+pub(super) fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::retry::{RetryBehavior, RetryPolicy, parse_retry_after};
+    use std::time::Duration;
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(policy.delay(RetryBehavior::Fail, 0, None), None);
+        assert_eq!(
+            policy.delay(RetryBehavior::Fixed, 3, None),
+            Some(Duration::from_secs(2))
+        );
+
+        let delays = (0..6)
+            .map(|retries| policy.delay(RetryBehavior::Backoff, retries, None).unwrap())
+            .map(|delay| delay.as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, vec![2, 4, 8, 16, 32, 60]);
+    }
+
+    #[test]
+    fn test_delay_retry_after() {
+        let policy = RetryPolicy::default();
+
+        assert_eq!(
+            policy.delay(RetryBehavior::Backoff, 0, Some(Duration::from_secs(10))),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            policy.delay(RetryBehavior::Fixed, 0, Some(Duration::from_secs(600))),
+            Some(Duration::from_secs(60))
+        );
+
+        let policy = RetryPolicy {
+            honor_retry_after: false,
+            ..RetryPolicy::default()
+        };
+        assert_eq!(
+            policy.delay(RetryBehavior::Fixed, 0, Some(Duration::from_secs(10))),
+            Some(Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn test_delay_jitter() {
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..RetryPolicy::default()
+        };
+
+        for _ in 0..100 {
+            let delay = policy.delay(RetryBehavior::Fixed, 0, None).unwrap();
+            assert!(delay >= Duration::from_secs(1));
+            assert!(delay <= Duration::from_secs(3));
+        }
+
+        for jitter in [f64::NAN, f64::INFINITY, -1.0] {
+            let policy = RetryPolicy { jitter, ..policy };
+            assert_eq!(
+                policy.delay(RetryBehavior::Fixed, 0, None),
+                Some(Duration::from_secs(2))
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+    }
+}
@@ -0,0 +1,146 @@
+//! The response from the `/search` endpoint.
+use crate::xml::ValueAttribute;
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn from_xml(xml: &str) -> error::Result<Vec<SearchResult>> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("Search XML: {}", xml);
+
+    xml::check_errors("search", xml)?;
+    Ok(xml::from_str::<ItemsXml>("search", xml)?
+        .inner
+        .into_iter()
+        .map(SearchResult::from)
+        .collect())
+}
+
+/// The type of things to search for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchType {
+    BoardGame,
+    BoardGameExpansion,
+    BoardGameAccessory,
+    RpgItem,
+    VideoGame,
+}
+
+impl SearchType {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            SearchType::BoardGame => "boardgame",
+            SearchType::BoardGameExpansion => "boardgameexpansion",
+            SearchType::BoardGameAccessory => "boardgameaccessory",
+            SearchType::RpgItem => "rpgitem",
+            SearchType::VideoGame => "videogame",
+        }
+    }
+}
+
+/// A search result.
+///
+/// `name` is the name that matched the query, which is not necessarily the primary name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SearchResult {
+    pub id: u32,
+    pub thing_type: String,
+    pub name: String,
+    pub is_primary_name: bool,
+    pub year_published: Option<i32>,
+}
+
+/// Sorts `results` so that exact primary name matches are first, then exact alternate name
+/// matches, then the rest. Matching ignores case, and the order is otherwise unchanged.
+pub(super) fn rank_by_name(results: &mut [SearchResult], name: &str) {
+    let name = name.to_lowercase();
+    results.sort_by_key(|result| {
+        match (result.name.to_lowercase() == name, result.is_primary_name) {
+            (true, true) => 0,
+            (true, false) => 1,
+            (false, _) => 2,
+        }
+    });
+}
+
+#[derive(Deserialize)]
+struct ItemsXml {
+    #[serde(rename = "item", default)]
+    inner: Vec<ItemXml>,
+}
+
+#[derive(Deserialize)]
+struct ItemXml {
+    #[serde(rename = "@id")]
+    id: u32,
+    #[serde(rename = "@type")]
+    thing_type: String,
+    name: NameXml,
+    #[serde(rename = "yearpublished")]
+    year_published: Option<ValueAttribute>,
+}
+
+// example: <name type="primary" value="CATAN"/>
+#[derive(Deserialize)]
+struct NameXml {
+    #[serde(rename = "@type")]
+    r#type: String,
+    #[serde(rename = "@value")]
+    value: String,
+}
+
+impl From<ItemXml> for SearchResult {
+    fn from(item: ItemXml) -> Self {
+        Self {
+            id: item.id,
+            thing_type: item.thing_type,
+            name: item.name.value,
+            is_primary_name: item.name.r#type == "primary",
+            year_published: item
+                .year_published
+                .and_then(ValueAttribute::into_option)
+                .and_then(|year| year.parse().ok()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::search::{from_xml, rank_by_name};
+    use std::fs;
+
+    #[test]
+    fn test_from_xml() {
+        let results = from_xml(&fs::read_to_string("test/search-catan.xml").expect("Reading file"))
+            .expect("Parsing XML");
+
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[1].id, 13);
+        assert_eq!(results[1].thing_type, "boardgame");
+        assert_eq!(results[1].name, "CATAN");
+        assert!(results[1].is_primary_name);
+        assert_eq!(results[1].year_published, Some(1995));
+        assert!(!results[2].is_primary_name);
+        assert_eq!(results[3].thing_type, "boardgameexpansion");
+        assert_eq!(results[4].year_published, None);
+    }
+
+    #[test]
+    fn test_from_xml_empty() {
+        let results = from_xml(&fs::read_to_string("test/search-empty.xml").expect("Reading file"))
+            .expect("Parsing XML");
+
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_rank_by_name() {
+        let mut results =
+            from_xml(&fs::read_to_string("test/search-catan.xml").expect("Reading file"))
+                .expect("Parsing XML");
+
+        rank_by_name(&mut results, "catan");
+
+        let ids = results.iter().map(|result| result.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![13, 278, 27710, 926, 999999]);
+    }
+}
@@ -0,0 +1,408 @@
+//! An in-process mock of the XML API, to test code using [`crate::BggClient`] offline.
+//!
+//! Start a [`MockServer`], script its responses with [`MockServer::on`], and point a client at
+//! [`MockServer::url`]. Afterwards, [`MockServer::requests`] has the requests it received.
+//!
+//! To test with real responses instead, record them once with [`RecordingTransport`], and
+//! replay them with [`ReplayTransport`].
+pub use crate::testing::cassette::{RecordingTransport, ReplayTransport};
+use log::debug;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use ureq::http::StatusCode;
+
+mod cassette;
+
+/// A response of a [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    /// How long to wait before responding.
+    pub delay: Duration,
+}
+
+impl MockResponse {
+    /// A `200 OK` response with `body`.
+    pub fn ok(body: &str) -> Self {
+        Self {
+            body: body.to_owned(),
+            ..Self::status(200)
+        }
+    }
+
+    /// A `200 OK` response with the contents of the file at `path`, e.g. one of the files in
+    /// `test/`.
+    pub fn fixture(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::ok(&fs::read_to_string(path)?))
+    }
+
+    /// A response with `status` and an empty body, e.g. `202` for a queued request, `429` or
+    /// `500`.
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: String::new(),
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Adds the header `name: value`, e.g. `Retry-After`.
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Waits `delay` before responding, e.g. to test timeouts.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct ReceivedRequest {
+    /// The path and query, e.g. `/xmlapi2/thing?id=13&stats=1`.
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl ReceivedRequest {
+    /// Returns the value of the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// An HTTP server on a local port that responds like the XML API, with scripted responses.
+///
+/// Responses are set by path with [`Self::on`]. Requests for paths without responses get
+/// `404 Not Found`. The server stops when it is dropped.
+pub struct MockServer {
+    address: SocketAddr,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct State {
+    responses: HashMap<String, VecDeque<MockResponse>>,
+    requests: Vec<ReceivedRequest>,
+}
+
+impl MockServer {
+    /// Starts a [`MockServer`] on a free local port.
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = state.clone();
+            let stopped = stopped.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else {
+                        continue;
+                    };
+                    // Each connection has its own thread, so delayed responses don't delay
+                    // concurrent requests.
+                    let state = state.clone();
+                    thread::spawn(move || {
+                        if let Err(error) = handle_connection(stream, &state) {
+                            debug!("Mock server connection error: {error}");
+                        }
+                    });
+                }
+            })
+        };
+
+        Ok(Self {
+            address,
+            state,
+            stopped,
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the base URL of the server, for [`crate::BggClient::from_url`].
+    pub fn url(&self) -> String {
+        format!("http://{}", self.address)
+    }
+
+    /// Responds to requests for `path` with `responses`, in order. The last response is repeated
+    /// once the others were used.
+    ///
+    /// `path` matches any query, e.g. `/xmlapi2/thing`, unless it has a query itself, e.g.
+    /// `/xmlapi2/thing?id=13&stats=1`, which only matches that exact query. Exact matches are
+    /// used first. Setting responses for the same `path` again replaces them.
+    ///
+    /// # Panics
+    /// If `responses` is empty.
+    pub fn on(&self, path: &str, responses: impl IntoIterator<Item = MockResponse>) {
+        let responses = responses.into_iter().collect::<VecDeque<_>>();
+        assert!(!responses.is_empty(), "responses must not be empty");
+
+        self.lock().responses.insert(path.to_owned(), responses);
+    }
+
+    /// Returns the requests received so far, in order.
+    pub fn requests(&self) -> Vec<ReceivedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        crate::lock(&self.state)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Wake up the listener thread, which is waiting for a connection.
+        let _ = TcpStream::connect(self.address);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl State {
+    fn respond(&mut self, url: &str) -> MockResponse {
+        let path = url.split('?').next().unwrap_or(url);
+        let responses = match self.responses.get_mut(url) {
+            Some(responses) => Some(responses),
+            None => self.responses.get_mut(path),
+        };
+
+        match responses {
+            Some(responses) if responses.len() > 1 => responses.pop_front().unwrap(),
+            Some(responses) => responses[0].clone(),
+            None => MockResponse::status(404),
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let url = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_owned();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+
+    let response = {
+        let mut state = crate::lock(state);
+        state.requests.push(ReceivedRequest {
+            url: url.clone(),
+            headers,
+        });
+        state.respond(&url)
+    };
+
+    thread::sleep(response.delay);
+    write_response(stream, &response)
+}
+
+fn write_response(mut stream: TcpStream, response: &MockResponse) -> io::Result<()> {
+    let reason = StatusCode::from_u16(response.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or_default();
+
+    let mut head = format!(
+        "HTTP/1.1 {} {reason}\r\nContent-Type: text/xml; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{name}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+
+    stream.write_all(head.as_bytes())?;
+    stream.write_all(response.body.as_bytes())?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::{HttpError, NotFound, RetriesExhausted};
+    use crate::testing::{MockResponse, MockServer};
+    use crate::{BggClient, HotType, RetryBehavior, RetryPolicy};
+    use std::time::Duration;
+
+    fn client(server: &MockServer) -> BggClient {
+        BggClient::from_url(&server.url(), "API_TOKEN").with_retry_policy(RetryPolicy {
+            base_delay: Duration::ZERO,
+            server_errors: RetryBehavior::Fixed,
+            ..RetryPolicy::default()
+        })
+    }
+
+    #[test]
+    fn test_queued() {
+        let server = MockServer::start().expect("Starting server");
+        server.on(
+            "/xmlapi2/collection",
+            [
+                MockResponse::status(202),
+                MockResponse::status(202),
+                MockResponse::fixture("test/unixgeek.xml").expect("Reading fixture"),
+            ],
+        );
+
+        let items = client(&server)
+            .get_collection("unixgeek", false)
+            .expect("Getting collection");
+
+        assert_eq!(items.len(), 3);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(
+            requests[0].url,
+            "/xmlapi2/collection?username=unixgeek&own=1&brief=1&subtype=boardgame&excludesubtype=boardgameexpansion"
+        );
+        assert_eq!(
+            requests[0].header("authorization"),
+            Some("Bearer API_TOKEN")
+        );
+    }
+
+    #[test]
+    fn test_throttled_and_server_errors() {
+        let server = MockServer::start().expect("Starting server");
+        server.on(
+            "/xmlapi2/hot",
+            [
+                MockResponse::status(429).with_header("Retry-After", "0"),
+                MockResponse::status(500),
+                MockResponse::fixture("test/hot-boardgame.xml").expect("Reading fixture"),
+            ],
+        );
+
+        let hot = client(&server)
+            .get_hot(HotType::BoardGame)
+            .expect("Getting hot list");
+
+        assert_eq!(hot.len(), 3);
+        assert_eq!(server.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_retries_exhausted() {
+        let server = MockServer::start().expect("Starting server");
+        server.on("/xmlapi2/hot", [MockResponse::status(503)]);
+
+        let result = client(&server).get_hot(HotType::BoardGame);
+
+        assert!(matches!(
+            result,
+            Err(RetriesExhausted {
+                attempts: 6,
+                last_status: Some(503),
+                ..
+            })
+        ));
+        assert_eq!(server.requests().len(), 6);
+    }
+
+    #[test]
+    fn test_exact_query_and_not_found() {
+        let server = MockServer::start().expect("Starting server");
+        server.on(
+            "/xmlapi2/thing",
+            [MockResponse::fixture("test/eclipse.xml").expect("Reading fixture")],
+        );
+        server.on(
+            "/xmlapi2/thing?id=430350&stats=1",
+            [MockResponse::fixture("test/enormity.xml").expect("Reading fixture")],
+        );
+        let client = client(&server);
+
+        let games = client.get_games(&[430350]).expect("Getting games");
+        assert_eq!(games[0].name, "Enormity");
+        let games = client.get_games(&[246900]).expect("Getting games");
+        assert_eq!(games[0].name, "Eclipse: Second Dawn for the Galaxy");
+
+        assert!(matches!(client.get_guild(1, false), Err(NotFound)));
+    }
+
+    #[test]
+    fn test_batches() {
+        let server = MockServer::start().expect("Starting server");
+        server.on("/xmlapi2/thing", [MockResponse::ok("<items/>")]);
+        let ids = (1..=25).collect::<Vec<_>>();
+
+        client(&server).get_games(&ids).expect("Getting games");
+
+        let urls = server
+            .requests()
+            .into_iter()
+            .map(|request| request.url)
+            .collect::<Vec<_>>();
+        assert_eq!(urls.len(), 2);
+        assert!(urls[0].starts_with("/xmlapi2/thing?id=1,2,"));
+        assert!(urls[1].starts_with("/xmlapi2/thing?id=21,22,23,24,25&"));
+    }
+
+    #[test]
+    fn test_delay() {
+        let server = MockServer::start().expect("Starting server");
+        server.on(
+            "/xmlapi2/hot",
+            [MockResponse::fixture("test/hot-boardgame.xml")
+                .expect("Reading fixture")
+                .with_delay(Duration::from_millis(500))],
+        );
+        let client = BggClient::builder()
+            .with_base_url(&server.url())
+            .with_read_timeout(Duration::from_millis(50))
+            .build()
+            .expect("Building client")
+            .with_retry_policy(RetryPolicy::no_retries());
+
+        let result = client.get_hot(HotType::BoardGame);
+
+        assert!(matches!(result, Err(HttpError(_))));
+    }
+}
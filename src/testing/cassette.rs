@@ -0,0 +1,291 @@
+//! Recording responses to a cassette file, and replaying them.
+//!
+//! A cassette is a text file with a header line, then each request URL and its response:
+//! ```text
+//! bgg-lib cassette 1
+//! request https://boardgamegeek.com/xmlapi2/hot?type=boardgame
+//! status 200
+//! header Content-Type: text/xml; charset=utf-8
+//! body 42
+//! <42 bytes of body>
+//! ```
+//! Request headers are not recorded, so the API token doesn't end up in the cassette.
+use crate::error;
+use crate::error::Error::HttpError;
+use crate::transport::{HttpRequest, HttpResponse, Transport};
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const HEADER: &str = "bgg-lib cassette 1";
+
+/// A request URL and the response to it.
+#[derive(Clone, Debug)]
+struct Interaction {
+    url: String,
+    response: HttpResponse,
+}
+
+/// A [`Transport`] that sends requests with another transport, and saves every response to a
+/// cassette file for [`ReplayTransport`].
+///
+/// The cassette is written after every response, so it is complete even if the program doesn't
+/// finish. Requests that fail without a response are not recorded.
+///
+/// ```no_run
+/// # use bgg_lib::{BggClient, UreqTransport};
+/// # use bgg_lib::testing::RecordingTransport;
+/// let transport = RecordingTransport::new("test/unixgeek.cassette", UreqTransport::default());
+/// let client = BggClient::with_transport("https://boardgamegeek.com", "API_TOKEN", transport);
+/// ```
+pub struct RecordingTransport {
+    path: PathBuf,
+    transport: Box<dyn Transport>,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl RecordingTransport {
+    /// Creates a [`RecordingTransport`] that records to `path`, replacing any cassette already
+    /// there, and sends requests with `transport`.
+    pub fn new(path: impl Into<PathBuf>, transport: impl Transport + 'static) -> Self {
+        Self {
+            path: path.into(),
+            transport: Box::new(transport),
+            interactions: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn get(&self, request: &HttpRequest) -> error::Result<HttpResponse> {
+        let response = self.transport.get(request)?;
+
+        let mut interactions = crate::lock(&self.interactions);
+        interactions.push(Interaction {
+            url: request.url.clone(),
+            response: response.clone(),
+        });
+        fs::write(&self.path, to_cassette(&interactions))
+            .map_err(|error| HttpError(error.into()))?;
+
+        Ok(response)
+    }
+}
+
+/// A [`Transport`] that responds with the responses in a cassette file from
+/// [`RecordingTransport`], without sending any requests.
+///
+/// Responses to the same URL are replayed in the order they were recorded, e.g. a
+/// `202 Accepted` and then the `200 OK` that followed it. Once they were all replayed, requests
+/// for that URL fail with [`error::Error::HttpError`], as do requests for URLs that were not
+/// recorded.
+pub struct ReplayTransport {
+    responses: Mutex<HashMap<String, VecDeque<HttpResponse>>>,
+}
+
+impl ReplayTransport {
+    /// Creates a [`ReplayTransport`] from the cassette at `path`.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut responses = HashMap::<_, VecDeque<_>>::new();
+        for interaction in from_cassette(&fs::read_to_string(path)?)? {
+            responses
+                .entry(interaction.url)
+                .or_default()
+                .push_back(interaction.response);
+        }
+
+        Ok(Self {
+            responses: Mutex::new(responses),
+        })
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn get(&self, request: &HttpRequest) -> error::Result<HttpResponse> {
+        crate::lock(&self.responses)
+            .get_mut(&request.url)
+            .and_then(VecDeque::pop_front)
+            .ok_or_else(|| HttpError(format!("No recorded response for {}", request.url).into()))
+    }
+}
+
+fn to_cassette(interactions: &[Interaction]) -> String {
+    let mut cassette = format!("{HEADER}\n");
+    for Interaction { url, response } in interactions {
+        cassette.push_str(&format!("request {url}\nstatus {}\n", response.status));
+        for (name, value) in &response.headers {
+            cassette.push_str(&format!("header {name}: {value}\n"));
+        }
+        cassette.push_str(&format!(
+            "body {}\n{}\n",
+            response.body.len(),
+            response.body
+        ));
+    }
+
+    cassette
+}
+
+fn from_cassette(mut cassette: &str) -> io::Result<Vec<Interaction>> {
+    if next_line(&mut cassette)? != HEADER {
+        return Err(invalid("Not a cassette"));
+    }
+
+    let mut interactions = Vec::new();
+    while !cassette.is_empty() {
+        let url = field(next_line(&mut cassette)?, "request")?.to_owned();
+        let status = field(next_line(&mut cassette)?, "status")?
+            .parse()
+            .map_err(|_| invalid("Invalid status"))?;
+
+        let mut headers = Vec::new();
+        let body_length = loop {
+            let line = next_line(&mut cassette)?;
+            if let Ok(length) = field(line, "body") {
+                break length
+                    .parse::<usize>()
+                    .map_err(|_| invalid("Invalid body length"))?;
+            }
+            let (name, value) = field(line, "header")?
+                .split_once(": ")
+                .ok_or_else(|| invalid("Invalid header"))?;
+            headers.push((name.to_owned(), value.to_owned()));
+        };
+
+        let body = cassette
+            .get(..body_length)
+            .ok_or_else(|| invalid("Body is too short"))?;
+        cassette = cassette[body_length..]
+            .strip_prefix('\n')
+            .ok_or_else(|| invalid("Body is too long"))?;
+
+        interactions.push(Interaction {
+            url,
+            response: HttpResponse {
+                status,
+                headers,
+                body: body.to_owned(),
+            },
+        });
+    }
+
+    Ok(interactions)
+}
+
+/// Removes the next line from `cassette` and returns it.
+fn next_line<'a>(cassette: &mut &'a str) -> io::Result<&'a str> {
+    let (line, rest) = cassette
+        .split_once('\n')
+        .ok_or_else(|| invalid("Unexpected end of cassette"))?;
+    *cassette = rest;
+    Ok(line)
+}
+
+/// Returns the value of a `name value` line.
+fn field<'a>(line: &'a str, name: &str) -> io::Result<&'a str> {
+    line.strip_prefix(name)
+        .and_then(|value| value.strip_prefix(' '))
+        .ok_or_else(|| invalid(&format!("Expected {name}, got: {line}")))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::HttpError;
+    use crate::testing::cassette::{Interaction, from_cassette, to_cassette};
+    use crate::testing::{MockResponse, MockServer, RecordingTransport, ReplayTransport};
+    use crate::{BggClient, HttpResponse, RetryPolicy, UreqTransport};
+    use std::fs;
+    use std::time::Duration;
+
+    #[test]
+    fn test_cassette() {
+        let interactions = vec![
+            Interaction {
+                url: "http://localhost/xmlapi2/collection?username=unixgeek".to_owned(),
+                response: HttpResponse {
+                    status: 202,
+                    headers: vec![("Retry-After".to_owned(), "1".to_owned())],
+                    body: String::new(),
+                },
+            },
+            Interaction {
+                url: "http://localhost/xmlapi2/thing?id=13".to_owned(),
+                response: HttpResponse {
+                    status: 200,
+                    headers: Vec::new(),
+                    body: "<items>\nCatan: Das Würfelspiel\n</items>\n".to_owned(),
+                },
+            },
+        ];
+
+        let parsed = from_cassette(&to_cassette(&interactions)).expect("Parsing cassette");
+
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].url, interactions[0].url);
+        assert_eq!(parsed[0].response.status, 202);
+        assert_eq!(parsed[0].response.header("retry-after"), Some("1"));
+        assert_eq!(parsed[1].response.body, interactions[1].response.body);
+
+        assert!(from_cassette("").is_err());
+        assert!(from_cassette("not a cassette\n").is_err());
+        assert!(
+            from_cassette("bgg-lib cassette 1\nrequest /\nstatus 200\nbody 10\n<a/>\n").is_err()
+        );
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("bgg-lib-test-{}.cassette", std::process::id()));
+        let retry_policy = RetryPolicy {
+            base_delay: Duration::ZERO,
+            ..RetryPolicy::default()
+        };
+
+        let server = MockServer::start().expect("Starting server");
+        server.on(
+            "/xmlapi2/collection",
+            [
+                MockResponse::status(202),
+                MockResponse::fixture("test/unixgeek.xml").expect("Reading fixture"),
+            ],
+        );
+        let client = BggClient::with_transport(
+            &server.url(),
+            "API_TOKEN",
+            RecordingTransport::new(&path, UreqTransport::default()),
+        )
+        .with_retry_policy(retry_policy.clone());
+        let recorded = client
+            .get_collection("unixgeek", false)
+            .expect("Getting collection");
+        drop(server);
+
+        let cassette = fs::read_to_string(&path).expect("Reading cassette");
+        assert!(!cassette.contains("API_TOKEN"));
+        let client = BggClient::with_transport(
+            &client.url,
+            "API_TOKEN",
+            ReplayTransport::from_file(&path).expect("Reading cassette"),
+        )
+        .with_retry_policy(retry_policy);
+        let replayed = client
+            .get_collection("unixgeek", false)
+            .expect("Getting collection");
+
+        assert_eq!(replayed.len(), recorded.len());
+        assert_eq!(replayed[0].id, recorded[0].id);
+        assert!(matches!(
+            client.get_collection("unixgeek", false),
+            Err(HttpError(_))
+        ));
+
+        fs::remove_file(path).expect("Removing cassette");
+    }
+}
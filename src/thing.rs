@@ -4,32 +4,114 @@
 //! used in this module and submodules. For example, the response XML has `items` as the root with
 //! `item` children elements.
 use crate::error;
-use crate::error::Error::XmlError;
-use crate::thing::thing1::{Item, Items};
+use crate::error::Error::ParseError;
+use crate::thing::thing1::Item;
 pub use thing2::Game;
 
 mod thing1;
 mod thing2;
 mod xslt;
 
+/// The games that could be got, and the ones that could not.
+///
+/// See [`crate::BggClient::get_games_partial`].
+#[derive(Default)]
+pub struct PartialGames {
+    pub games: Vec<Game>,
+    pub failed: Vec<FailedGames>,
+}
+
+impl PartialGames {
+    /// Returns the ids of the games that could not be got.
+    pub fn failed_ids(&self) -> Vec<u32> {
+        self.failed
+            .iter()
+            .flat_map(|failed| failed.ids.iter().copied())
+            .collect()
+    }
+}
+
+/// Games that could not be got, and why.
+///
+/// An item that could not be parsed has its own id. A request that failed has all of the ids
+/// requested together.
+#[derive(Debug)]
+pub struct FailedGames {
+    pub ids: Vec<u32>,
+    pub error: error::Error,
+}
+
 pub(super) fn from_xml(xml: &str) -> error::Result<Vec<Game>> {
+    let mut partial = from_xml_partial(xml)?;
+
+    if partial.failed.is_empty() {
+        Ok(partial.games)
+    } else {
+        Err(partial.failed.swap_remove(0).error)
+    }
+}
+
+/// Like [`from_xml`], but an item that can't be parsed doesn't fail the others.
+pub(super) fn from_xml_partial(xml: &str) -> error::Result<PartialGames> {
     #[cfg(feature = "moar-debug")]
     log::debug!("Things XML: {}", xml);
 
-    let items: Vec<Item> = serde_xml_rs::from_str::<Items>(&xslt::transform(xml)?)
-        .map_err(|error| XmlError(format!("Error deserializing xml: {error}")))?
-        .into_inner();
+    let mut partial = PartialGames::default();
+    for (id, item_xml) in split_items(&xslt::transform(xml)?)? {
+        let game = serde_xml_rs::from_str::<Item>(&item_xml)
+            .map_err(|error| parse_error(Some(id), error))
+            .and_then(Game::try_from);
+        match game {
+            Ok(game) => partial.games.push(game),
+            Err(error) => partial.failed.push(FailedGames {
+                ids: vec![id],
+                error,
+            }),
+        }
+    }
 
-    let mut games = Vec::new();
-    for item in items {
-        games.push(item.try_into()?);
+    Ok(partial)
+}
+
+/// Splits the `<items>` document into the XML of each `<item>`, with its id.
+fn split_items(xml: &str) -> error::Result<Vec<(u32, String)>> {
+    let document = libxml::parser::Parser::default()
+        .parse_string(xml)
+        .map_err(|error| parse_error(None, error))?;
+    let root = document
+        .get_root_element()
+        .ok_or_else(|| parse_error(None, "No root element"))?;
+
+    Ok(root
+        .get_child_elements()
+        .iter()
+        .filter(|node| node.get_name() == "item")
+        .map(|node| {
+            let id = node
+                .get_property("id")
+                .and_then(|id| id.parse().ok())
+                .unwrap_or_default();
+            (id, document.node_to_string(node))
+        })
+        .collect())
+}
+
+/// Returns a [`ParseError`] for the `/thing` endpoint.
+fn parse_error(
+    item_id: Option<u32>,
+    source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+) -> error::Error {
+    ParseError {
+        endpoint: "thing",
+        item_id,
+        source: source.into(),
     }
-    Ok(games)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::thing::from_xml;
+    use crate::error::Error::ParseError;
+    use crate::thing::{from_xml, from_xml_partial};
     use std::fs;
 
     #[test]
@@ -47,4 +129,30 @@ mod tests {
         assert_eq!(game.best_player_counts.len(), 0);
         assert_eq!(game.rating, 7.894);
     }
+
+    #[test]
+    fn test_from_xml_partial() {
+        let xml = fs::read_to_string("test/things-bad-poll.xml").expect("Reading file");
+
+        let partial = from_xml_partial(&xml).expect("Parsing XML");
+
+        assert_eq!(partial.games.len(), 1);
+        assert_eq!(partial.games[0].id, 430350);
+        assert_eq!(partial.failed_ids(), vec![246900]);
+        assert!(matches!(
+            partial.failed[0].error,
+            ParseError {
+                endpoint: "thing",
+                item_id: Some(246900),
+                ..
+            }
+        ));
+        assert_eq!(
+            partial.failed[0].error.to_string(),
+            "Error parsing thing item 246900: Could not parse player count: six"
+        );
+
+        let result = from_xml(&xml);
+        assert!(result.is_err());
+    }
 }
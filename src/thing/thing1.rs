@@ -4,12 +4,15 @@ use serde::{Deserialize, Deserializer};
 use std::result;
 use std::str::FromStr;
 
+// Items are deserialized one at a time, so one bad item doesn't fail the others. This is for tests.
+#[cfg(test)]
 #[derive(Deserialize)]
 pub(super) struct Items {
     #[serde(rename = "item")]
     inner: Vec<Item>,
 }
 
+#[cfg(test)]
 impl Items {
     pub(super) fn into_inner(self) -> Vec<Item> {
         self.inner
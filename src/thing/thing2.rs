@@ -5,7 +5,7 @@
 //! documentation states the result of the endpoint are "thing items", so maybe this should be
 //! called `Thing` or `Item`, but we only care about board games, so we use `Game`.
 use crate::error;
-use crate::error::Error::XmlApiError;
+use crate::thing::parse_error;
 use crate::thing::thing1::{Category, Item, Results};
 use serde::{Deserialize, Serialize};
 
@@ -72,17 +72,20 @@ impl TryFrom<Item> for Game {
                 }
             // There may be other variants of numplayers strings we are not aware of.
             } else {
-                return Err(XmlApiError(format!(
-                    "Could not parse player count: {}",
-                    poll_results.player_count
-                )));
+                return Err(parse_error(
+                    Some(item.id),
+                    format!(
+                        "Could not parse player count: {}",
+                        poll_results.player_count
+                    ),
+                ));
             }
         }
 
         let name = if let Some(name) = item.names.into_iter().find(|n| n.r#type == "primary") {
             name.value
         } else {
-            return Err(XmlApiError("No primary name found".to_owned()));
+            return Err(parse_error(Some(item.id), "No primary name found"));
         };
 
         Ok(Self {
@@ -0,0 +1,117 @@
+//! The HTTP layer used by [`crate::BggClient`].
+//!
+//! [`UreqTransport`] is used by default. Implement [`Transport`] to use a different HTTP client,
+//! or to record or fake responses.
+use crate::error;
+use std::time::Duration;
+use ureq::Agent;
+
+/// An HTTP `GET` request.
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// The longest the whole request may take, if the call has a deadline.
+    pub timeout: Option<Duration>,
+}
+
+/// An HTTP response.
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// Returns the value of the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Sends HTTP requests for [`crate::BggClient`].
+///
+/// Implementations should return responses of any status, rather than an error, since the client
+/// handles statuses like `202 Accepted` and `429 Too Many Requests` itself. Errors should be
+/// reserved for failing to get a response at all, and be wrapped in
+/// [`error::Error::HttpError`].
+pub trait Transport: Send + Sync {
+    fn get(&self, request: &HttpRequest) -> error::Result<HttpResponse>;
+}
+
+/// The default [`Transport`], using `ureq`.
+pub struct UreqTransport {
+    agent: Agent,
+}
+
+impl UreqTransport {
+    /// Creates a [`UreqTransport`] using `agent`.
+    ///
+    /// The agent must be configured with `http_status_as_error(false)`.
+    pub fn from_agent(agent: Agent) -> Self {
+        Self { agent }
+    }
+}
+
+impl Default for UreqTransport {
+    fn default() -> Self {
+        Self::from_agent(
+            Agent::config_builder()
+                .http_status_as_error(false)
+                .user_agent(crate::user_agent())
+                .build()
+                .into(),
+        )
+    }
+}
+
+impl Transport for UreqTransport {
+    fn get(&self, request: &HttpRequest) -> error::Result<HttpResponse> {
+        let mut builder = self.agent.get(&request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(timeout) = request.timeout {
+            builder = builder.config().timeout_global(Some(timeout)).build();
+        }
+        let mut response = builder.call()?;
+
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.to_string(),
+                    value.to_str().unwrap_or("not a string").to_owned(),
+                )
+            })
+            .collect();
+
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            headers,
+            body: response.body_mut().read_to_string()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::transport::HttpResponse;
+
+    #[test]
+    fn test_header() {
+        let response = HttpResponse {
+            status: 429,
+            headers: vec![("Retry-After".to_owned(), "10".to_owned())],
+            body: String::new(),
+        };
+
+        assert_eq!(response.header("retry-after"), Some("10"));
+        assert_eq!(response.header("content-type"), None);
+    }
+}
@@ -0,0 +1,313 @@
+//! The response from the `/user` endpoint.
+use crate::error::Error::{InvalidUserError, ParseError};
+use crate::xml::ValueAttribute;
+use crate::{error, xml};
+use serde::{Deserialize, Serialize};
+
+pub(super) fn from_xml(xml: &str) -> error::Result<User> {
+    #[cfg(feature = "moar-debug")]
+    log::debug!("User XML: {}", xml);
+
+    xml::check_errors("user", xml)?;
+    xml::from_str::<UserXml>("user", xml)?.try_into()
+}
+
+/// The domain of the `hot` and `top` lists of a [`User`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Domain {
+    #[default]
+    BoardGame,
+    Rpg,
+    VideoGame,
+}
+
+impl Domain {
+    fn as_str(self) -> &'static str {
+        match self {
+            Domain::BoardGame => "boardgame",
+            Domain::Rpg => "rpg",
+            Domain::VideoGame => "videogame",
+        }
+    }
+}
+
+/// The optional parts of the `/user` response.
+///
+/// `buddies` and `guilds` are paginated by the XML API, 100 per page, so use `page` to get the
+/// rest of them.
+#[derive(Clone, Debug)]
+pub struct UserOptions {
+    pub buddies: bool,
+    pub guilds: bool,
+    pub hot: bool,
+    pub top: bool,
+    pub domain: Domain,
+    pub page: u32,
+}
+
+impl Default for UserOptions {
+    fn default() -> Self {
+        Self {
+            buddies: false,
+            guilds: false,
+            hot: false,
+            top: false,
+            domain: Domain::default(),
+            page: 1,
+        }
+    }
+}
+
+impl UserOptions {
+    pub(super) fn to_query(&self) -> String {
+        let flag = |name: &str, enabled: bool| {
+            if enabled {
+                format!("&{name}=1")
+            } else {
+                String::new()
+            }
+        };
+
+        format!(
+            "{buddies}{guilds}{hot}{top}&domain={domain}&page={page}",
+            buddies = flag("buddies", self.buddies),
+            guilds = flag("guilds", self.guilds),
+            hot = flag("hot", self.hot),
+            top = flag("top", self.top),
+            domain = self.domain.as_str(),
+            page = self.page
+        )
+    }
+}
+
+/// Represents a user.
+///
+/// Missing values, including an avatar of `N/A`, are `None`. The lists are only `Some` when
+/// requested with [`UserOptions`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct User {
+    pub id: u32,
+    pub name: String,
+    pub avatar: Option<String>,
+    pub year_registered: Option<u16>,
+    pub last_login: Option<String>,
+    pub state_or_province: Option<String>,
+    pub country: Option<String>,
+    pub web_address: Option<String>,
+    pub trade_rating: i32,
+    pub buddies: Option<UserPage<Buddy>>,
+    pub guilds: Option<UserPage<UserGuild>>,
+    pub hot: Option<Vec<RankedItem>>,
+    pub top: Option<Vec<RankedItem>>,
+}
+
+/// A page of a paginated list of a [`User`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserPage<T> {
+    pub total: u32,
+    pub page: u32,
+    pub items: Vec<T>,
+}
+
+/// A buddy of a [`User`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Buddy {
+    #[serde(rename = "@id")]
+    pub id: u32,
+    #[serde(rename = "@name")]
+    pub name: String,
+}
+
+/// A guild a [`User`] belongs to.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserGuild {
+    #[serde(rename = "@id")]
+    pub id: u32,
+    #[serde(rename = "@name")]
+    pub name: String,
+}
+
+/// An item in the `hot` or `top` list of a [`User`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RankedItem {
+    #[serde(rename = "@rank")]
+    pub rank: u32,
+    #[serde(rename = "@id")]
+    pub id: u32,
+    #[serde(rename = "@name")]
+    pub name: String,
+}
+
+#[derive(Deserialize)]
+struct UserXml {
+    // Empty if the user does not exist.
+    #[serde(rename = "@id")]
+    id: String,
+    #[serde(rename = "@name")]
+    name: String,
+    #[serde(rename = "avatarlink")]
+    avatar: ValueAttribute,
+    #[serde(rename = "yearregistered")]
+    year_registered: ValueAttribute,
+    #[serde(rename = "lastlogin")]
+    last_login: ValueAttribute,
+    #[serde(rename = "stateorprovince")]
+    state_or_province: ValueAttribute,
+    country: ValueAttribute,
+    #[serde(rename = "webaddress")]
+    web_address: ValueAttribute,
+    #[serde(rename = "traderating")]
+    trade_rating: ValueAttribute,
+    buddies: Option<BuddiesXml>,
+    guilds: Option<GuildsXml>,
+    hot: Option<RankedItemsXml>,
+    top: Option<RankedItemsXml>,
+}
+
+#[derive(Deserialize)]
+struct BuddiesXml {
+    #[serde(rename = "@total")]
+    total: u32,
+    #[serde(rename = "@page")]
+    page: u32,
+    #[serde(rename = "buddy", default)]
+    inner: Vec<Buddy>,
+}
+
+#[derive(Deserialize)]
+struct GuildsXml {
+    #[serde(rename = "@total")]
+    total: u32,
+    #[serde(rename = "@page")]
+    page: u32,
+    #[serde(rename = "guild", default)]
+    inner: Vec<UserGuild>,
+}
+
+#[derive(Deserialize)]
+struct RankedItemsXml {
+    #[serde(rename = "item", default)]
+    inner: Vec<RankedItem>,
+}
+
+impl TryFrom<UserXml> for User {
+    type Error = error::Error;
+
+    fn try_from(user: UserXml) -> Result<Self, error::Error> {
+        // The XML API responds with a user that has no id, rather than an error.
+        if user.id.is_empty() {
+            return Err(InvalidUserError);
+        }
+
+        let id = user.id.parse().map_err(|error| ParseError {
+            endpoint: "user",
+            item_id: None,
+            source: Box::new(error),
+        })?;
+        let year_registered = user
+            .year_registered
+            .into_option()
+            .and_then(|year| year.parse().ok());
+        let trade_rating = user.trade_rating.value.parse().unwrap_or_default();
+
+        Ok(Self {
+            id,
+            name: user.name,
+            avatar: user.avatar.into_option(),
+            year_registered,
+            last_login: user.last_login.into_option(),
+            state_or_province: user.state_or_province.into_option(),
+            country: user.country.into_option(),
+            web_address: user.web_address.into_option(),
+            trade_rating,
+            buddies: user.buddies.map(|buddies| UserPage {
+                total: buddies.total,
+                page: buddies.page,
+                items: buddies.inner,
+            }),
+            guilds: user.guilds.map(|guilds| UserPage {
+                total: guilds.total,
+                page: guilds.page,
+                items: guilds.inner,
+            }),
+            hot: user.hot.map(|hot| hot.inner),
+            top: user.top.map(|top| top.inner),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::Error::InvalidUserError;
+    use crate::user::{Domain, UserOptions, from_xml};
+    use std::fs;
+
+    #[test]
+    fn test_from_xml() {
+        let user = from_xml(&fs::read_to_string("test/unixgeek-user.xml").expect("Reading file"))
+            .expect("Parsing XML");
+
+        assert_eq!(user.id, 1234567);
+        assert_eq!(user.name, "unixgeek");
+        assert_eq!(user.avatar, None);
+        assert_eq!(user.year_registered, Some(2008));
+        assert_eq!(user.last_login.as_deref(), Some("2024-12-02"));
+        assert_eq!(user.state_or_province.as_deref(), Some("Virginia"));
+        assert_eq!(user.country.as_deref(), Some("United States"));
+        assert_eq!(user.web_address, None);
+        assert_eq!(user.trade_rating, 3);
+
+        let buddies = user.buddies.unwrap();
+        assert_eq!(buddies.total, 2);
+        assert_eq!(buddies.page, 1);
+        assert_eq!(buddies.items.len(), 2);
+        assert_eq!(buddies.items[1].id, 1000002);
+        assert_eq!(buddies.items[1].name, "dicetower fan");
+
+        let guilds = user.guilds.unwrap();
+        assert_eq!(guilds.items.len(), 1);
+        assert_eq!(guilds.items[0].id, 1299);
+
+        let top = user.top.unwrap();
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].rank, 1);
+        assert_eq!(top[0].id, 421);
+        assert_eq!(top[0].name, "1830: Railways & Robber Barons");
+
+        let hot = user.hot.unwrap();
+        assert_eq!(hot.len(), 1);
+        assert_eq!(hot[0].id, 430350);
+    }
+
+    #[test]
+    fn test_from_xml_invalid_user_error() {
+        let result = from_xml(&fs::read_to_string("test/invalid-user.xml").expect("Reading file"));
+
+        assert!(matches!(result, Err(InvalidUserError)));
+    }
+
+    #[test]
+    fn test_from_xml_errors() {
+        let result =
+            from_xml(&fs::read_to_string("test/invalid-username.xml").expect("Reading file"));
+
+        assert!(matches!(result, Err(InvalidUserError)));
+    }
+
+    #[test]
+    fn test_options_to_query() {
+        assert_eq!(
+            UserOptions::default().to_query(),
+            "&domain=boardgame&page=1"
+        );
+
+        let options = UserOptions {
+            buddies: true,
+            top: true,
+            domain: Domain::Rpg,
+            page: 2,
+            ..UserOptions::default()
+        };
+        assert_eq!(options.to_query(), "&buddies=1&top=1&domain=rpg&page=2");
+    }
+}
@@ -0,0 +1,107 @@
+//! Helpers shared by the endpoint modules for deserializing the XML API responses.
+use crate::error;
+use crate::error::Error::{InvalidUserError, ParseError, XmlApiError};
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes `xml`, the response from `endpoint`, with serde, mapping any failure to
+/// [`ParseError`].
+pub(crate) fn from_str<'a, T: Deserialize<'a>>(
+    endpoint: &'static str,
+    xml: &str,
+) -> error::Result<T> {
+    serde_xml_rs::from_str::<T>(xml).map_err(|error| ParseError {
+        endpoint,
+        item_id: None,
+        source: Box::new(error),
+    })
+}
+
+/// Returns an error if the XML API responded with an `<errors>` document instead of data.
+pub(crate) fn check_errors(endpoint: &'static str, xml: &str) -> error::Result<()> {
+    if !xml.contains("<errors>") {
+        return Ok(());
+    }
+
+    let errors = from_str::<ErrorResponses>(endpoint, xml)?.inner;
+
+    if errors.len() == 1 && errors[0].message == "Invalid username specified" {
+        Err(InvalidUserError)
+    } else {
+        let message = errors
+            .into_iter()
+            .map(|error| error.message)
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(XmlApiError(message))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "error")]
+struct ErrorResponse {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponses {
+    #[serde(rename = "error")]
+    inner: Vec<ErrorResponse>,
+}
+
+// example: <yearregistered value="2008"/>
+#[derive(Deserialize)]
+pub(crate) struct ValueAttribute {
+    #[serde(rename = "@value", default)]
+    pub(crate) value: String,
+}
+
+impl ValueAttribute {
+    /// The value, or `None` if it is empty or `N/A`.
+    pub(crate) fn into_option(self) -> Option<String> {
+        match self.value.trim() {
+            "" | "N/A" => None,
+            _ => Some(self.value),
+        }
+    }
+}
+
+/// Deserializes the text of an element, treating an empty element as `None`.
+pub(crate) fn empty_as_none<'de, D: Deserializer<'de>>(d: D) -> Result<Option<String>, D::Error> {
+    let s: Option<String> = Deserialize::deserialize(d)?;
+    Ok(s.filter(|s| !s.trim().is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xml::{ErrorResponses, ValueAttribute, from_str};
+    use std::fs;
+
+    #[test]
+    fn test_deserialize_error_response() {
+        let error_responses: ErrorResponses = serde_xml_rs::from_str(
+            &fs::read_to_string("test/invalid-username.xml").expect("Reading file"),
+        )
+        .expect("Parsing XML");
+
+        assert_eq!(error_responses.inner.len(), 1);
+        assert_eq!(
+            error_responses.inner[0].message,
+            "Invalid username specified"
+        );
+    }
+
+    #[test]
+    fn test_value_attribute_into_option() {
+        let value: ValueAttribute =
+            from_str("user", r#"<avatarlink value="N/A"/>"#).expect("Parsing XML");
+        assert_eq!(value.into_option(), None);
+
+        let value: ValueAttribute =
+            from_str("user", r#"<country value=""/>"#).expect("Parsing XML");
+        assert_eq!(value.into_option(), None);
+
+        let value: ValueAttribute =
+            from_str("user", r#"<country value="United States"/>"#).expect("Parsing XML");
+        assert_eq!(value.into_option(), Some("United States".to_owned()));
+    }
+}